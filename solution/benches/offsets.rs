@@ -0,0 +1,43 @@
+// Offset representation benchmark
+//
+// Times `best_move` on a fixed mid-game position. Run it with and without
+// `--features compact-offsets` and compare: the chosen moves must match,
+// only the time per move may differ.
+
+use solution::GameState;
+use std::time::Instant;
+
+const BOARD_SIZE: usize = 60;
+const ITERATIONS: u32 = 20;
+
+/// Mid-game frame: two blobs facing each other and a 4x3 piece
+fn frame() -> String {
+    let mut text = format!("$$$ exec p1 : [bench]\nAnfield {} {}:\n    {}\n", BOARD_SIZE, BOARD_SIZE, "0".repeat(BOARD_SIZE));
+    for y in 0..BOARD_SIZE {
+        let row: String = (0..BOARD_SIZE)
+            .map(|x| match (x, y) {
+                _ if (10..25).contains(&x) && (10..20).contains(&y) => '@',
+                _ if (35..50).contains(&x) && (38..50).contains(&y) => '$',
+                _ => '.',
+            })
+            .collect();
+        text.push_str(&format!("{:03} {}\n", y, row));
+    }
+    text.push_str("Piece 4 3:\n.##.\n###.\n..##\n");
+    text
+}
+
+fn main() {
+    let mut state = GameState::new();
+    let (offsets, trim_x, trim_y) = state.parse_frame(&frame()).expect("benchmark frame parses");
+    let expected = state.best_move(&offsets, trim_x, trim_y);
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        assert_eq!(state.best_move(&offsets, trim_x, trim_y), expected);
+    }
+    let per_move = start.elapsed() / ITERATIONS;
+
+    let representation = if cfg!(feature = "compact-offsets") { "compact (i8)" } else { "wide (i32)" };
+    println!("{} offsets: {:?} per move, move {:?}", representation, per_move, expected);
+}
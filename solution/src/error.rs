@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Errors produced while parsing engine input
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// A dimensions line (`Anfield` or `Piece`) could not be read
+    BadDimensions(String),
+    /// A board or piece row is shorter than the declared width
+    RowTooShort { expected: usize, got: usize },
+    /// A board row contains a glyph that is not a cell symbol
+    UnknownBoardGlyph(char),
+    /// A piece row contains a glyph that is not a piece symbol
+    UnknownPieceGlyph(char),
+    /// A piece row has a filled cell past the declared piece width
+    PieceWidthMismatch { width: usize, glyph: char },
+    /// In strict mode, a board row's gutter number is not the row being parsed
+    RowNumberMismatch { expected: usize, gutter: String },
+    /// A zone overlay does not have the board's dimensions
+    ZoneSizeMismatch { expected: (usize, usize), got: (usize, usize) },
+    /// A piece glyph was configured as both filled and empty
+    AmbiguousPieceGlyph(char),
+    /// Declared board or piece dimensions exceed `max_board_dim`
+    DimensionsTooLarge { width: usize, height: usize, max: usize },
+    /// A frame passed to `parse_frame` ended before this part was read
+    IncompleteFrame(&'static str),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::BadDimensions(line) => write!(f, "Invalid dimensions line: {}", line),
+            ParseError::RowTooShort { expected, got } => {
+                write!(f, "Row too short: expected {} cells, got {}", expected, got)
+            }
+            ParseError::UnknownBoardGlyph(ch) => write!(f, "Unknown board cell: {}", ch),
+            ParseError::UnknownPieceGlyph(ch) => write!(f, "Unknown piece cell: {}", ch),
+            ParseError::PieceWidthMismatch { width, glyph } => {
+                write!(f, "Piece row has filled cell '{}' past width {}", glyph, width)
+            }
+            ParseError::RowNumberMismatch { expected, gutter } => {
+                write!(f, "Board row {} is labeled '{}'", expected, gutter)
+            }
+            ParseError::ZoneSizeMismatch { expected, got } => {
+                write!(f, "Zone overlay is {}x{}, board is {}x{}", got.0, got.1, expected.0, expected.1)
+            }
+            ParseError::AmbiguousPieceGlyph(ch) => {
+                write!(f, "Piece glyph '{}' is configured as both filled and empty", ch)
+            }
+            ParseError::DimensionsTooLarge { width, height, max } => {
+                write!(f, "Dimensions {}x{} exceed the {}x{} limit", width, height, max, max)
+            }
+            ParseError::IncompleteFrame(part) => write!(f, "Frame ended before the {}", part),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
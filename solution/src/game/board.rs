@@ -0,0 +1,168 @@
+use crate::types::Cell;
+use super::packed_board::PackedBoard;
+
+/// Board storage of the game state, and of boards simulated from it
+///
+/// Rows of cells by default; with the `packed-board` feature a `PackedBoard`,
+/// so a huge board is held only in packed form.
+#[cfg(not(feature = "packed-board"))]
+pub type Board = Vec<Vec<Cell>>;
+/// Board storage of the game state, and of boards simulated from it
+///
+/// Rows of cells by default; with the `packed-board` feature a `PackedBoard`,
+/// so a huge board is held only in packed form.
+#[cfg(feature = "packed-board")]
+pub type Board = PackedBoard;
+
+/// Cell access shared by the board representations
+///
+/// Scoring and validation read boards only through this, so they work the
+/// same on either backend.
+pub trait Grid {
+    /// All-empty board of the given size
+    fn empty(width: usize, height: usize) -> Self;
+
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    /// Cell at (x, y); callers must stay within the board
+    fn at(&self, x: usize, y: usize) -> Cell;
+
+    /// Overwrite the cell at (x, y); callers must stay within the board
+    fn put(&mut self, x: usize, y: usize, cell: Cell);
+
+    /// Drop every row from `height` on
+    fn truncate_rows(&mut self, height: usize);
+
+    /// Cell at (x, y), or `None` outside the board
+    fn get_checked(&self, x: usize, y: usize) -> Option<Cell> {
+        if x < self.width() && y < self.height() {
+            Some(self.at(x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Every cell with its coordinates, row by row
+    fn cells(&self) -> impl Iterator<Item = (usize, usize, Cell)> + '_
+    where
+        Self: Sized,
+    {
+        let width = self.width();
+        (0..self.height()).flat_map(move |y| (0..width).map(move |x| (x, y, self.at(x, y))))
+    }
+}
+
+impl Grid for Vec<Vec<Cell>> {
+    fn empty(width: usize, height: usize) -> Self {
+        vec![vec![Cell::Empty; width]; height]
+    }
+
+    fn width(&self) -> usize {
+        self.first().map_or(0, |row| row.len())
+    }
+
+    fn height(&self) -> usize {
+        self.len()
+    }
+
+    fn at(&self, x: usize, y: usize) -> Cell {
+        self[y][x]
+    }
+
+    fn put(&mut self, x: usize, y: usize, cell: Cell) {
+        self[y][x] = cell;
+    }
+
+    fn truncate_rows(&mut self, height: usize) {
+        self.truncate(height);
+    }
+}
+
+impl Grid for PackedBoard {
+    fn empty(width: usize, height: usize) -> Self {
+        PackedBoard::new(width, height)
+    }
+
+    fn width(&self) -> usize {
+        PackedBoard::width(self)
+    }
+
+    fn height(&self) -> usize {
+        PackedBoard::height(self)
+    }
+
+    fn at(&self, x: usize, y: usize) -> Cell {
+        self.get(x, y)
+    }
+
+    fn put(&mut self, x: usize, y: usize, cell: Cell) {
+        self.set(x, y, cell);
+    }
+
+    fn truncate_rows(&mut self, height: usize) {
+        self.truncate_height(height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameState;
+
+    const ROWS: [&str; 4] = ["..@...$", "@@..$$.", ".......", "$...@.@"];
+
+    fn cell_for(ch: char) -> Cell {
+        match ch {
+            '@' => Cell::Player1,
+            '$' => Cell::Player2,
+            _ => Cell::Empty,
+        }
+    }
+
+    fn fill<G: Grid>() -> G {
+        let mut grid = G::empty(ROWS[0].len(), ROWS.len());
+        for (y, row) in ROWS.iter().enumerate() {
+            for (x, ch) in row.chars().enumerate() {
+                grid.put(x, y, cell_for(ch));
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn packed_and_unpacked_hold_the_same_cells() {
+        let rows: Vec<Vec<Cell>> = fill();
+        let packed: PackedBoard = fill();
+        assert_eq!((Grid::width(&packed), Grid::height(&packed)), (rows.width(), rows.height()));
+        assert!(rows.cells().eq(packed.cells()));
+        assert_eq!(PackedBoard::from_rows(&rows), packed);
+        assert_eq!(packed.to_rows(), rows);
+        assert_eq!(packed.get_checked(7, 0), None);
+        assert_eq!(packed.get_checked(2, 0), Some(Cell::Player1));
+    }
+
+    #[test]
+    fn truncated_packed_board_equals_a_fresh_one() {
+        let mut packed: PackedBoard = fill();
+        Grid::truncate_rows(&mut packed, 1);
+        let mut rows: Vec<Vec<Cell>> = fill();
+        rows.truncate_rows(1);
+        assert_eq!(packed, PackedBoard::from_rows(&rows));
+    }
+
+    #[test]
+    fn parsed_board_renders_back_identically() {
+        let mut state = GameState::new();
+        state.parse_board_dimensions("Anfield 7 4:").unwrap();
+        for (y, row) in ROWS.iter().enumerate() {
+            state.parse_board_row(&format!("{:03} {}", y, row), y).unwrap();
+        }
+        let expected: String = std::iter::once("Anfield 7 4:\n    0123456\n".to_string())
+            .chain(ROWS.iter().enumerate().map(|(y, row)| format!("{:03} {}\n", y, row)))
+            .collect();
+        assert_eq!(state.render_board(), expected);
+        assert!(state.board.cells().eq(fill::<Board>().cells()));
+    }
+}
@@ -0,0 +1,34 @@
+use super::game_state::GameState;
+use super::board::{Board, Grid};
+
+impl GameState {
+    /// Cells that became the opponent's since `previous` (their last move)
+    ///
+    /// Cells outside `previous` (e.g. the first board of a game) count as new.
+    pub fn diff_opponent_move(&self, previous: &Board) -> Vec<(usize, usize)> {
+        let op = self.opponent_cell();
+        let mut claimed = Vec::new();
+        for (x, y, cell) in self.board.cells() {
+            if cell == op && previous.get_checked(x, y) != Some(op) {
+                claimed.push((x, y));
+            }
+        }
+        claimed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::board_state;
+
+    #[test]
+    fn diff_finds_the_opponent_move_between_two_boards() {
+        let before = board_state(&["@.....", "......", "....$$"]);
+        let after = board_state(&["@@....", "...$..", "...$$$"]);
+        assert_eq!(after.diff_opponent_move(&before.board), vec![(3, 1), (3, 2)]);
+        // Our own growth is not theirs, and a first board counts entirely
+        assert_eq!(before.diff_opponent_move(&before.board), vec![]);
+        assert_eq!(before.diff_opponent_move(&Board::empty(0, 0)), vec![(4, 2), (5, 2)]);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::types::{Cell, PieceOffset};
+use super::game_state::{to_board_index, GameState};
+use super::board::{Board, Grid};
+
+impl GameState {
+    /// Bounding box of our territory as (min_x, min_y, max_x, max_y)
+    pub fn my_territory_bbox(&self) -> Option<(usize, usize, usize, usize)> {
+        let my = self.my_cell();
+        let mut bbox: Option<(usize, usize, usize, usize)> = None;
+        for (x, y, cell) in self.board.cells() {
+            if cell == my {
+                bbox = Some(match bbox {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    None => (x, y, x, y),
+                });
+            }
+        }
+        bbox
+    }
+
+    /// Share of our bounding box that we actually own (1.0 for a solid block)
+    pub fn territory_bbox_fill_ratio(&self) -> f32 {
+        match self.my_territory_bbox() {
+            Some((x0, y0, x1, y1)) => {
+                let area = (x1 - x0 + 1) * (y1 - y0 + 1);
+                self.count_my_territory() as f32 / area as f32
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Share of the convex hull of our cells (as unit squares) that we own
+    pub fn territory_hull_fill_ratio(&self) -> f32 {
+        let my = self.my_cell();
+        let mut corners = Vec::new();
+        for (x, y, cell) in self.board.cells() {
+            if cell == my {
+                let (x, y) = (x as i64, y as i64);
+                corners.extend_from_slice(&[(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]);
+            }
+        }
+        if corners.is_empty() {
+            return 0.0;
+        }
+
+        let hull = convex_hull(corners);
+        // Shoelace formula, doubled to stay in integers
+        let mut area2 = 0;
+        for i in 0..hull.len() {
+            let (ax, ay) = hull[i];
+            let (bx, by) = hull[(i + 1) % hull.len()];
+            area2 += ax * by - bx * ay;
+        }
+        let area = area2.abs() as f32 / 2.0;
+        if area == 0.0 {
+            return 0.0;
+        }
+        self.count_my_territory() as f32 / area
+    }
+
+    /// Bounding-box fill ratio our territory would have after this placement
+    pub fn fill_ratio_after(&self, x: i32, y: i32, piece_offsets: &[PieceOffset], new_cells: i32) -> f32 {
+        let mut bbox = self.my_territory_bbox();
+        let placed = piece_offsets
+            .iter()
+            .filter_map(|off| to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height));
+        for (bx, by) in placed {
+            bbox = Some(match bbox {
+                Some((x0, y0, x1, y1)) => (x0.min(bx), y0.min(by), x1.max(bx), y1.max(by)),
+                None => (bx, by, bx, by),
+            });
+        }
+        match bbox {
+            Some((x0, y0, x1, y1)) => {
+                let area = (x1 - x0 + 1) * (y1 - y0 + 1);
+                (self.count_my_territory() + new_cells) as f32 / area as f32
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Check whether the empty cell (x, y) is a hole in `board_after` that the move created
+    ///
+    /// A hole is an empty cell with our territory on all four sides; cells on
+    /// the board edge never count, and neither do holes that already existed.
+    pub fn would_create_enclosed_hole(&self, board_after: &Board, x: usize, y: usize) -> bool {
+        if board_after.at(x, y) != Cell::Empty || x == 0 || y == 0 || x + 1 >= self.board_width || y + 1 >= self.board_height {
+            return false;
+        }
+        let my = self.my_cell();
+        let neighbors = [(x, y + 1), (x + 1, y), (x, y - 1), (x - 1, y)];
+        neighbors.iter().all(|&(nx, ny)| board_after.at(nx, ny) == my)
+            && !neighbors.iter().all(|&(nx, ny)| self.board.at(nx, ny) == my)
+    }
+
+    /// Number of holes the placement at this anchor would leave in our territory
+    pub fn enclosed_holes_created(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        let after = self.simulate_move(x, y, piece_offsets);
+        let mut holes: Vec<(usize, usize)> = Vec::new();
+        for off in piece_offsets {
+            for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+                let nx = x + off.dx() + dx;
+                let ny = y + off.dy() + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if !holes.contains(&(nx, ny)) && self.would_create_enclosed_hole(&after, nx, ny) {
+                    holes.push((nx, ny));
+                }
+            }
+        }
+        holes.len() as i32
+    }
+}
+
+/// Andrew's monotone chain; returns the hull in counter-clockwise order
+fn convex_hull(mut points: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    points.sort_unstable();
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: (i64, i64), a: (i64, i64), b: (i64, i64)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+
+    let mut hull: Vec<(i64, i64)> = Vec::with_capacity(points.len() * 2);
+    // Lower hull
+    for &p in points.iter() {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // Upper hull, never popping back into the lower one
+    let lower_len = hull.len() + 1;
+    for &p in points.iter().rev().skip(1) {
+        while hull.len() >= lower_len && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    hull.pop();
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::testing::board_state;
+
+    #[test]
+    fn hull_fill_ratio_separates_scattered_from_solid() {
+        let solid = board_state(&["......", ".@@@..", ".@@@..", ".@@@..", ".....$"]);
+        assert!((solid.territory_hull_fill_ratio() - 1.0).abs() < 1e-6);
+
+        let scattered = board_state(&["@....@", "......", "......", "......", "@....$"]);
+        assert!(scattered.territory_hull_fill_ratio() < 0.2, "{}", scattered.territory_hull_fill_ratio());
+        assert!(scattered.territory_bbox_fill_ratio() < 0.2);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::types::{CaptureRule, ScanOrder, Strategy, TieBreak};
+use super::game_state::GameState;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The tunable scoring knobs of a `GameState`, detached from any board
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoringConfig {
+    pub heat_weight: i32,
+    pub expansion_weight: i32,
+    pub blocking_weight: i32,
+    pub compactness_weight: i32,
+    pub early_phase_end: f32,
+    pub late_phase_start: f32,
+    pub frontier_weight: i32,
+    pub tie_break: TieBreak,
+    pub path_cost_weight: i32,
+    pub fill_ratio_weight: i32,
+    pub overlap_frontier_weight: i32,
+    pub frontier_length_weight: i32,
+    pub mirror_weight: i32,
+    pub safe_cell_weight: i32,
+    pub centrality_weight: i32,
+    pub anchor_region_weight: i32,
+    pub mobility_weight: i32,
+    pub mobility_floor: i32,
+    pub unlock_weight: i32,
+    pub hole_weight: i32,
+    pub region_capture_weight: i32,
+    pub orientation_weight: i32,
+    pub contested_frontier_weight: i32,
+    pub wall_lead_percent: i32,
+    pub seed_barrier_weight: i32,
+    pub seed_barrier_turns: i32,
+    pub exposed_frontier_weight: i32,
+    pub max_reach_radius: Option<i32>,
+    pub frontier_openness_weight: i32,
+    pub interior_overlap_weight: i32,
+    pub connectivity_weight: i32,
+    pub aggression_curve: Vec<(f32, f32)>,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        GameState::new().scoring_config()
+    }
+}
+
+impl GameState {
+    /// Snapshot of the current scoring knobs
+    pub fn scoring_config(&self) -> ScoringConfig {
+        ScoringConfig {
+            heat_weight: self.heat_weight,
+            expansion_weight: self.expansion_weight,
+            blocking_weight: self.blocking_weight,
+            compactness_weight: self.compactness_weight,
+            early_phase_end: self.early_phase_end,
+            late_phase_start: self.late_phase_start,
+            frontier_weight: self.frontier_weight,
+            tie_break: self.tie_break,
+            path_cost_weight: self.path_cost_weight,
+            fill_ratio_weight: self.fill_ratio_weight,
+            overlap_frontier_weight: self.overlap_frontier_weight,
+            frontier_length_weight: self.frontier_length_weight,
+            mirror_weight: self.mirror_weight,
+            safe_cell_weight: self.safe_cell_weight,
+            centrality_weight: self.centrality_weight,
+            anchor_region_weight: self.anchor_region_weight,
+            mobility_weight: self.mobility_weight,
+            mobility_floor: self.mobility_floor,
+            unlock_weight: self.unlock_weight,
+            hole_weight: self.hole_weight,
+            region_capture_weight: self.region_capture_weight,
+            orientation_weight: self.orientation_weight,
+            contested_frontier_weight: self.contested_frontier_weight,
+            wall_lead_percent: self.wall_lead_percent,
+            seed_barrier_weight: self.seed_barrier_weight,
+            seed_barrier_turns: self.seed_barrier_turns,
+            exposed_frontier_weight: self.exposed_frontier_weight,
+            max_reach_radius: self.max_reach_radius,
+            frontier_openness_weight: self.frontier_openness_weight,
+            interior_overlap_weight: self.interior_overlap_weight,
+            connectivity_weight: self.connectivity_weight,
+            aggression_curve: self.aggression_curve.clone(),
+        }
+    }
+
+    /// Replace the scoring knobs with those of a profile
+    pub fn apply_scoring_config(&mut self, config: &ScoringConfig) {
+        self.heat_weight = config.heat_weight;
+        self.expansion_weight = config.expansion_weight;
+        self.blocking_weight = config.blocking_weight;
+        self.compactness_weight = config.compactness_weight;
+        self.early_phase_end = config.early_phase_end;
+        self.late_phase_start = config.late_phase_start;
+        self.frontier_weight = config.frontier_weight;
+        self.tie_break = config.tie_break;
+        self.path_cost_weight = config.path_cost_weight;
+        self.fill_ratio_weight = config.fill_ratio_weight;
+        self.overlap_frontier_weight = config.overlap_frontier_weight;
+        self.frontier_length_weight = config.frontier_length_weight;
+        self.mirror_weight = config.mirror_weight;
+        self.safe_cell_weight = config.safe_cell_weight;
+        self.centrality_weight = config.centrality_weight;
+        self.anchor_region_weight = config.anchor_region_weight;
+        self.mobility_weight = config.mobility_weight;
+        self.mobility_floor = config.mobility_floor;
+        self.unlock_weight = config.unlock_weight;
+        self.hole_weight = config.hole_weight;
+        self.region_capture_weight = config.region_capture_weight;
+        self.orientation_weight = config.orientation_weight;
+        self.contested_frontier_weight = config.contested_frontier_weight;
+        self.wall_lead_percent = config.wall_lead_percent;
+        self.seed_barrier_weight = config.seed_barrier_weight;
+        self.seed_barrier_turns = config.seed_barrier_turns;
+        self.exposed_frontier_weight = config.exposed_frontier_weight;
+        self.max_reach_radius = config.max_reach_radius;
+        self.frontier_openness_weight = config.frontier_openness_weight;
+        self.interior_overlap_weight = config.interior_overlap_weight;
+        self.connectivity_weight = config.connectivity_weight;
+        self.aggression_curve = config.aggression_curve.clone();
+    }
+
+    /// Build a game state from a TOML config file layered over the defaults
+    ///
+    /// Only the flat subset of TOML we need is understood: `key = value` pairs
+    /// with integer, float or quoted string values, `#` comments and `[section]`
+    /// headers (sections only group keys; names are global).
+    pub fn from_config_path(path: &Path) -> io::Result<GameState> {
+        let text = fs::read_to_string(path)?;
+        let mut state = GameState::new();
+        state.apply_config_str(&text)?;
+        Ok(state)
+    }
+
+    /// Apply `key = value` settings from TOML text on top of the current values
+    pub fn apply_config_str(&mut self, text: &str) -> io::Result<()> {
+        for (line_no, raw) in text.lines().enumerate() {
+            let line = strip_comment(raw).trim();
+            if line.is_empty() || (line.starts_with('[') && line.ends_with(']')) {
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((k, v)) => (k.trim(), v.trim()),
+                None => return Err(config_error(line_no, &format!("expected `key = value`, got `{}`", line))),
+            };
+            self.apply_config_value(key, value)
+                .map_err(|msg| config_error(line_no, &msg))?;
+        }
+        Ok(())
+    }
+
+    fn apply_config_value(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "heat_weight" => self.heat_weight = parse_number(key, value)?,
+            "expansion_weight" => self.expansion_weight = parse_number(key, value)?,
+            "blocking_weight" => self.blocking_weight = parse_number(key, value)?,
+            "compactness_weight" => self.compactness_weight = parse_number(key, value)?,
+            "frontier_weight" => self.frontier_weight = parse_number(key, value)?,
+            "path_cost_weight" => self.path_cost_weight = parse_number(key, value)?,
+            "fill_ratio_weight" => self.fill_ratio_weight = parse_number(key, value)?,
+            "overlap_frontier_weight" => self.overlap_frontier_weight = parse_number(key, value)?,
+            "frontier_length_weight" => self.frontier_length_weight = parse_number(key, value)?,
+            "mirror_weight" => self.mirror_weight = parse_number(key, value)?,
+            "safe_cell_weight" => self.safe_cell_weight = parse_number(key, value)?,
+            "centrality_weight" => self.centrality_weight = parse_number(key, value)?,
+            "anchor_region_weight" => self.anchor_region_weight = parse_number(key, value)?,
+            "mobility_weight" => self.mobility_weight = parse_number(key, value)?,
+            "mobility_floor" => self.mobility_floor = parse_number(key, value)?,
+            "unlock_weight" => self.unlock_weight = parse_number(key, value)?,
+            "hole_weight" => self.hole_weight = parse_number(key, value)?,
+            "region_capture_weight" => self.region_capture_weight = parse_number(key, value)?,
+            "orientation_weight" => self.orientation_weight = parse_number(key, value)?,
+            "contested_frontier_weight" => self.contested_frontier_weight = parse_number(key, value)?,
+            "wall_lead_percent" => self.wall_lead_percent = parse_number(key, value)?,
+            "seed_barrier_weight" => self.seed_barrier_weight = parse_number(key, value)?,
+            "seed_barrier_turns" => self.seed_barrier_turns = parse_number(key, value)?,
+            "exposed_frontier_weight" => self.exposed_frontier_weight = parse_number(key, value)?,
+            "max_reach_radius" => {
+                // 0 or less lifts the limit
+                let radius: i32 = parse_number(key, value)?;
+                self.max_reach_radius = if radius > 0 { Some(radius) } else { None };
+            }
+            "frontier_openness_weight" => self.frontier_openness_weight = parse_number(key, value)?,
+            "interior_overlap_weight" => self.interior_overlap_weight = parse_number(key, value)?,
+            "connectivity_weight" => self.connectivity_weight = parse_number(key, value)?,
+            "early_phase_end" => self.early_phase_end = parse_number(key, value)?,
+            "late_phase_start" => self.late_phase_start = parse_number(key, value)?,
+            "node_budget" => self.node_budget = parse_number(key, value)?,
+            "max_candidates" => self.max_candidates = parse_number(key, value)?,
+            "log_rejections" => self.log_rejections = parse_number(key, value)?,
+            "log_timing" => self.log_timing = parse_number(key, value)?,
+            "survival_threshold" => self.survival_threshold = parse_number(key, value)?,
+            "anti_mirror" => self.anti_mirror = parse_number(key, value)?,
+            "momentum_blocking" => self.momentum_blocking = parse_number(key, value)?,
+            "rotations_allowed" => self.rotations_allowed = parse_number(key, value)?,
+            "strict_row_numbers" => self.strict_row_numbers = parse_number(key, value)?,
+            "piece_indent" => self.piece_indent = parse_number(key, value)?,
+            "max_board_dim" => self.max_board_dim = parse_number(key, value)?,
+            "lenient_piece_rows" => self.lenient_piece_rows = parse_number(key, value)?,
+            "required_own_overlap" => self.required_own_overlap = parse_number(key, value)?,
+            "flip_y" => self.output_format.flip_y = parse_number(key, value)?,
+            "output_order" => {
+                self.output_format.swap_xy = match parse_string(key, value)?.as_str() {
+                    "xy" => false,
+                    "yx" => true,
+                    other => return Err(format!("unknown output_order `{}`", other)),
+                }
+            }
+            "tie_break" => {
+                self.tie_break = match parse_string(key, value)?.as_str() {
+                    "top_left" => TieBreak::TopLeft,
+                    "center_bias" => TieBreak::CenterBias,
+                    other => return Err(format!("unknown tie_break `{}`", other)),
+                }
+            }
+            "scan_order" => {
+                self.scan_order = match parse_string(key, value)?.as_str() {
+                    "row_major" => ScanOrder::RowMajor,
+                    "column_major" => ScanOrder::ColumnMajor,
+                    other => return Err(format!("unknown scan_order `{}`", other)),
+                }
+            }
+            "capture_rule" => {
+                self.capture_rule = match parse_string(key, value)?.as_str() {
+                    "forbidden" => CaptureRule::Forbidden,
+                    "allow_one" => CaptureRule::AllowOne,
+                    other => return Err(format!("unknown capture_rule `{}`", other)),
+                }
+            }
+            "strategy" => {
+                let name = parse_string(key, value)?;
+                self.strategy = Strategy::from_name(&name).ok_or_else(|| format!("unknown strategy `{}`", name))?;
+            }
+            _ => eprintln!("Ignoring unknown config key: {}", key),
+        }
+        Ok(())
+    }
+}
+
+/// Drop a trailing `#` comment that is not inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .replace('_', "")
+        .parse::<T>()
+        .map_err(|_| format!("invalid number for `{}`: {}", key, value))
+}
+
+fn parse_string(key: &str, value: &str) -> Result<String, String> {
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        Ok(value[1..value.len() - 1].to_string())
+    } else {
+        Err(format!("expected a quoted string for `{}`: {}", key, value))
+    }
+}
+
+fn config_error(line_no: usize, msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("config line {}: {}", line_no + 1, msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PieceOffset;
+
+    const SAMPLE: &str = "$$$ exec p1 : [bot]\nAnfield 9 9:\n    012345678\n\
+                          000 .........\n001 .........\n002 $........\n003 .........\n004 @...@....\n\
+                          005 .........\n006 .........\n007 .........\n008 .........\nPiece 2 1:\n##\n";
+
+    fn sample_move(state: &mut GameState) -> Option<(i32, i32)> {
+        let (offsets, tx, ty): (Vec<PieceOffset>, i32, i32) = state.parse_frame(SAMPLE).unwrap();
+        state.best_move(&offsets, tx, ty)
+    }
+
+    #[test]
+    fn config_file_settings_take_effect() {
+        let path = std::env::temp_dir().join(format!("filler-config-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "# tuned profile\n[weights]\ncentrality_weight = 10\nheat_weight = 1_500 # tuned\n\n\
+             [phases]\nearly_phase_end = 0.5\n\n[search]\nscan_order = \"column_major\"\n",
+        )
+        .unwrap();
+        let loaded = GameState::from_config_path(&path);
+        fs::remove_file(&path).unwrap();
+        let mut state = loaded.unwrap();
+
+        assert_eq!((state.centrality_weight, state.heat_weight), (10, 1500));
+        assert_eq!(state.early_phase_end, 0.5);
+        assert_eq!(state.scan_order, ScanOrder::ColumnMajor);
+        assert_eq!(sample_move(&mut GameState::new()), Some((0, 4)));
+        assert_eq!(sample_move(&mut state), Some((3, 4)));
+    }
+
+    #[test]
+    fn scan_order_orders_the_legal_moves() {
+        let mut state = GameState::new();
+        let vertical = SAMPLE.replace("Piece 2 1:\n##\n", "Piece 1 2:\n#\n#\n");
+        let (offsets, tx, ty) = state.parse_frame(&vertical).unwrap();
+        let row_major = state.find_legal_moves(&offsets, tx, ty);
+        state.apply_config_str("scan_order = \"column_major\"").unwrap();
+        let column_major = state.find_legal_moves(&offsets, tx, ty);
+
+        assert_eq!(row_major, vec![(0, 3), (4, 3), (0, 4), (4, 4)]);
+        assert_eq!(column_major, vec![(0, 3), (0, 4), (4, 3), (4, 4)]);
+        assert!(state.apply_config_str("scan_order = \"spiral\"").is_err());
+    }
+}
@@ -0,0 +1,548 @@
+use crate::types::{Cell, PieceOffset};
+use super::game_state::GameState;
+use super::board::{Board, Grid};
+use std::collections::VecDeque;
+
+/// How many steps of open space we look past a frontier cell when valuing it
+const FRONTIER_UNLOCK_DEPTH: i32 = 2;
+
+impl GameState {
+    /// Check whether an empty cell borders opponent territory (a legal expansion candidate for them)
+    pub fn is_opponent_frontier_cell(&self, x: usize, y: usize) -> bool {
+        self.is_opponent_frontier_cell_on(&self.board, x, y)
+    }
+
+    /// Same as `is_opponent_frontier_cell`, on an arbitrary board
+    pub fn is_opponent_frontier_cell_on(&self, board: &Board, x: usize, y: usize) -> bool {
+        if board.at(x, y) != Cell::Empty {
+            return false;
+        }
+        let op = self.opponent_cell();
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        for (dx, dy) in directions.iter() {
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            if nx >= 0 && nx < self.board_width as i32 && ny >= 0 && ny < self.board_height as i32
+                && board.at(nx as usize, ny as usize) == op {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Estimate how much open space claiming this cell would unlock for the opponent
+    ///
+    /// Counts empty cells within a few steps (walking through empty cells only)
+    /// that do not already border the opponent.
+    pub fn frontier_unlock_value(&self, x: usize, y: usize) -> i32 {
+        self.frontier_unlock_value_on(&self.board, x, y)
+    }
+
+    /// Same as `frontier_unlock_value`, on an arbitrary board
+    pub fn frontier_unlock_value_on(&self, board: &Board, x: usize, y: usize) -> i32 {
+        // The search never leaves a small diamond, so a short visited list beats a full grid
+        let mut seen = vec![(x, y)];
+        let mut queue = VecDeque::new();
+        let mut value = 0;
+
+        queue.push_back((x, y, 0));
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some((cx, cy, depth)) = queue.pop_front() {
+            if depth == FRONTIER_UNLOCK_DEPTH {
+                continue;
+            }
+            for (dx, dy) in directions.iter() {
+                let nx = cx as i32 + dx;
+                let ny = cy as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if board.at(nx, ny) != Cell::Empty || seen.contains(&(nx, ny)) {
+                    continue;
+                }
+                seen.push((nx, ny));
+                if !self.is_opponent_frontier_cell_on(board, nx, ny) {
+                    value += 1;
+                }
+                queue.push_back((nx, ny, depth + 1));
+            }
+        }
+
+        value
+    }
+
+    /// Opponent's likely next expansion targets: empty cells adjacent to their
+    /// territory, ranked by how much open space they would unlock (best first)
+    pub fn opponent_frontier(&self) -> Vec<(usize, usize)> {
+        let mut ranked = Vec::new();
+        for y in 0..self.board_height {
+            for x in 0..self.board_width {
+                if self.is_opponent_frontier_cell(x, y) {
+                    ranked.push(((x, y), self.frontier_unlock_value(x, y)));
+                }
+            }
+        }
+
+        // Highest unlock value first; ties keep scan order (row-major)
+        ranked.sort_by_key(|&(_, value)| std::cmp::Reverse(value));
+        ranked.into_iter().map(|(pos, _)| pos).collect()
+    }
+
+    /// Score a placement by the opponent frontier cells it denies
+    ///
+    /// A frontier cell we cover is denied outright and is worth the open space
+    /// it would have unlocked (plus itself). A nearby frontier cell we leave
+    /// empty but partly wall off is worth the open space it loses, found with
+    /// the same short local flood on the board after the move.
+    pub fn frontier_denial_score(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        if piece_offsets.is_empty() {
+            return 0;
+        }
+        let after = self.simulate_move(x, y, piece_offsets);
+
+        // Only frontier cells close enough for the placement to touch their flood can change
+        let reach = FRONTIER_UNLOCK_DEPTH + 1;
+        let min_x = piece_offsets.iter().map(|o| x + o.dx()).min().unwrap_or(x) - reach;
+        let max_x = piece_offsets.iter().map(|o| x + o.dx()).max().unwrap_or(x) + reach;
+        let min_y = piece_offsets.iter().map(|o| y + o.dy()).min().unwrap_or(y) - reach;
+        let max_y = piece_offsets.iter().map(|o| y + o.dy()).max().unwrap_or(y) + reach;
+
+        let mut denied = 0;
+        for fy in min_y.max(0)..=max_y.min(self.board_height as i32 - 1) {
+            for fx in min_x.max(0)..=max_x.min(self.board_width as i32 - 1) {
+                let (fx, fy) = (fx as usize, fy as usize);
+                if !self.is_opponent_frontier_cell(fx, fy) {
+                    continue;
+                }
+                let before = self.frontier_unlock_value(fx, fy);
+                if after.at(fx, fy) != Cell::Empty {
+                    denied += before + 1;
+                } else {
+                    denied += before - self.frontier_unlock_value_on(&after, fx, fy);
+                }
+            }
+        }
+        denied
+    }
+
+    /// Number of our cells bordering empty space (how many places we can grow from)
+    pub fn my_frontier_length(&self) -> usize {
+        self.my_frontier_length_on(&self.board)
+    }
+
+    /// Same as `my_frontier_length`, on an arbitrary board
+    pub fn my_frontier_length_on(&self, board: &Board) -> usize {
+        let mut length = 0;
+        for y in 0..self.board_height {
+            for x in 0..self.board_width {
+                if self.is_my_frontier_cell_on(board, x, y) {
+                    length += 1;
+                }
+            }
+        }
+        length
+    }
+
+    /// How blocky the open space near our territory is, in tenths
+    ///
+    /// Splits the empty cells within reach of us (see `my_reachability_on`)
+    /// into connected pockets and sums each pocket's area over its bounding-box
+    /// perimeter. A square pocket fits many piece shapes and scores high; a
+    /// corridor of the same area barely fits anything wider than a line.
+    pub fn frontier_openness_score(&self, board: &Board) -> i32 {
+        let near = self.my_reachability_on(board);
+        let mut seen = vec![vec![false; self.board_width]; self.board_height];
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let mut score = 0;
+        for sy in 0..self.board_height {
+            for sx in 0..self.board_width {
+                if !near[sy][sx] || seen[sy][sx] {
+                    continue;
+                }
+                seen[sy][sx] = true;
+                let mut queue = VecDeque::new();
+                queue.push_back((sx, sy));
+                let (mut area, mut min_x, mut max_x, mut min_y, mut max_y) = (0, sx, sx, sy, sy);
+                while let Some((x, y)) = queue.pop_front() {
+                    area += 1;
+                    min_x = min_x.min(x);
+                    max_x = max_x.max(x);
+                    min_y = min_y.min(y);
+                    max_y = max_y.max(y);
+                    for (dx, dy) in directions.iter() {
+                        let nx = x as i32 + dx;
+                        let ny = y as i32 + dy;
+                        if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                            continue;
+                        }
+                        let (nx, ny) = (nx as usize, ny as usize);
+                        if near[ny][nx] && !seen[ny][nx] {
+                            seen[ny][nx] = true;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+                let perimeter = 2 * ((max_x - min_x + 1) + (max_y - min_y + 1)) as i32;
+                score += area * 10 / perimeter;
+            }
+        }
+        score
+    }
+
+    /// Our frontier cells on a board, split into (safe, exposed)
+    ///
+    /// A frontier cell is exposed if one of its empty neighbors is reachable by
+    /// the opponent (`opponent_reach`, see `opponent_reachability_on`), and
+    /// safe if all of them are sealed off: those can be filled at leisure.
+    pub fn classify_frontier(&self, board: &Board, opponent_reach: &[Vec<bool>]) -> (usize, usize) {
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let (mut safe, mut exposed) = (0, 0);
+        for y in 0..self.board_height {
+            for x in 0..self.board_width {
+                if !self.is_my_frontier_cell_on(board, x, y) {
+                    continue;
+                }
+                let contested = directions.iter().any(|(dx, dy)| {
+                    let nx = x as i32 + dx;
+                    let ny = y as i32 + dy;
+                    nx >= 0 && ny >= 0 && nx < self.board_width as i32 && ny < self.board_height as i32
+                        && board.at(nx as usize, ny as usize) == Cell::Empty
+                        && opponent_reach[ny as usize][nx as usize]
+                });
+                if contested {
+                    exposed += 1;
+                } else {
+                    safe += 1;
+                }
+            }
+        }
+        (safe, exposed)
+    }
+
+    /// Check whether a cell is ours and has at least one empty neighbor
+    pub fn is_my_frontier_cell_on(&self, board: &Board, x: usize, y: usize) -> bool {
+        if board.at(x, y) != self.my_cell() {
+            return false;
+        }
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        directions.iter().any(|(dx, dy)| {
+            let nx = x as i32 + dx;
+            let ny = y as i32 + dy;
+            nx >= 0 && ny >= 0 && nx < self.board_width as i32 && ny < self.board_height as i32
+                && board.at(nx as usize, ny as usize) == Cell::Empty
+        })
+    }
+
+    /// Change in our frontier length if the piece were placed at this anchor
+    ///
+    /// Only the placed cells and their neighbors can change status, so just
+    /// those are compared.
+    pub fn frontier_length_delta(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        let after = self.simulate_move(x, y, piece_offsets);
+        let mut touched: Vec<(usize, usize)> = Vec::new();
+        for off in piece_offsets {
+            for (dx, dy) in [(0, 0), (0, 1), (1, 0), (0, -1), (-1, 0)] {
+                let nx = x + off.dx() + dx;
+                let ny = y + off.dy() + dy;
+                if nx >= 0 && ny >= 0 && nx < self.board_width as i32 && ny < self.board_height as i32
+                    && !touched.contains(&(nx as usize, ny as usize)) {
+                    touched.push((nx as usize, ny as usize));
+                }
+            }
+        }
+
+        touched
+            .into_iter()
+            .map(|(tx, ty)| {
+                self.is_my_frontier_cell_on(&after, tx, ty) as i32
+                    - self.is_my_frontier_cell_on(&self.board, tx, ty) as i32
+            })
+            .sum()
+    }
+
+    /// Rough count of our options next turn: empty cells bordering our territory
+    ///
+    /// Any piece we place must spill into at least one of these, so when the
+    /// count gets small we are close to being forced to pass.
+    pub fn next_turn_mobility_estimate(&self, board: &Board) -> usize {
+        let my = self.my_cell();
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let mut open = 0;
+        for y in 0..self.board_height {
+            for x in 0..self.board_width {
+                if board.at(x, y) != Cell::Empty {
+                    continue;
+                }
+                let touches_us = directions.iter().any(|(dx, dy)| {
+                    let nx = x as i32 + dx;
+                    let ny = y as i32 + dy;
+                    nx >= 0 && ny >= 0 && nx < self.board_width as i32 && ny < self.board_height as i32
+                        && board.at(nx as usize, ny as usize) == my
+                });
+                if touches_us {
+                    open += 1;
+                }
+            }
+        }
+        open
+    }
+
+    /// Approximate how many separate spots the opponent could anchor a small piece
+    ///
+    /// An anchor spot is an opponent frontier cell with room for a second cell
+    /// (an empty neighbor). Spots that touch each other, diagonals included (the
+    /// two sides of an opponent corner), form one region, so the result is the
+    /// number of connected groups of anchor spots.
+    pub fn opponent_anchor_regions(&self, board: &Board) -> usize {
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let is_anchor = |x: usize, y: usize| {
+            self.is_opponent_frontier_cell_on(board, x, y)
+                && directions.iter().any(|(dx, dy)| {
+                    let nx = x as i32 + dx;
+                    let ny = y as i32 + dy;
+                    nx >= 0 && ny >= 0 && nx < self.board_width as i32 && ny < self.board_height as i32
+                        && board.at(nx as usize, ny as usize) == Cell::Empty
+                })
+        };
+
+        let mut seen = vec![vec![false; self.board_width]; self.board_height];
+        let mut regions = 0;
+        for y in 0..self.board_height {
+            for x in 0..self.board_width {
+                if seen[y][x] || !is_anchor(x, y) {
+                    continue;
+                }
+                regions += 1;
+                seen[y][x] = true;
+                let mut queue = VecDeque::new();
+                queue.push_back((x, y));
+                while let Some((cx, cy)) = queue.pop_front() {
+                    for (dx, dy) in directions.iter().chain([(1, 1), (1, -1), (-1, 1), (-1, -1)].iter()) {
+                        let nx = cx as i32 + dx;
+                        let ny = cy as i32 + dy;
+                        if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                            continue;
+                        }
+                        let (nx, ny) = (nx as usize, ny as usize);
+                        if !seen[ny][nx] && is_anchor(nx, ny) {
+                            seen[ny][nx] = true;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+            }
+        }
+        regions
+    }
+
+    /// How many opponent anchor regions this placement would remove (negative if it splits one)
+    pub fn anchor_region_reduction(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        let after = self.simulate_move(x, y, piece_offsets);
+        self.opponent_anchor_regions(&self.board) as i32 - self.opponent_anchor_regions(&after) as i32
+    }
+
+    /// Opponent placements of a sample piece on a board (trimmed offsets)
+    ///
+    /// Standard rules from their side: exactly one of their cells covered and
+    /// none of ours.
+    pub fn opponent_legal_move_count_on(&self, board: &Board, sample_offsets: &[PieceOffset]) -> usize {
+        let width = sample_offsets.iter().map(|o| o.dx() + 1).max().unwrap_or(0);
+        let height = sample_offsets.iter().map(|o| o.dy() + 1).max().unwrap_or(0);
+        if width == 0 || width > self.board_width as i32 || height > self.board_height as i32 {
+            return 0;
+        }
+        let op = self.opponent_cell();
+        let mut count = 0;
+        for y in 0..=self.board_height as i32 - height {
+            for x in 0..=self.board_width as i32 - width {
+                let mut overlaps = 0;
+                let legal = sample_offsets.iter().all(|off| {
+                    let cell = board.at((x + off.dx()) as usize, (y + off.dy()) as usize);
+                    if cell == op {
+                        overlaps += 1;
+                    }
+                    cell == Cell::Empty || (cell == op && overlaps == 1)
+                });
+                if legal && overlaps == 1 {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Opponent placements of a sample piece that a board change takes away
+    ///
+    /// Blocking measured directly: their legal moves now minus their legal moves
+    /// on `board_after` (negative if the change opens space for them).
+    pub fn opponent_legal_move_delta(&self, board_after: &Board, sample_offsets: &[PieceOffset]) -> i32 {
+        self.opponent_legal_move_count_on(&self.board, sample_offsets) as i32
+            - self.opponent_legal_move_count_on(board_after, sample_offsets) as i32
+    }
+
+    /// Separate opponent regions on a board: groups of their cells joined
+    /// through their own cells and empty space
+    ///
+    /// Our cells are the walls between regions.
+    pub fn opponent_region_count(&self, board: &Board) -> usize {
+        let op = self.opponent_cell();
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let mut seen = vec![vec![false; self.board_width]; self.board_height];
+        let mut regions = 0;
+        for y in 0..self.board_height {
+            for x in 0..self.board_width {
+                if seen[y][x] || board.at(x, y) != op {
+                    continue;
+                }
+                regions += 1;
+                seen[y][x] = true;
+                let mut queue = VecDeque::new();
+                queue.push_back((x, y));
+                while let Some((cx, cy)) = queue.pop_front() {
+                    for (dx, dy) in directions.iter() {
+                        let nx = cx as i32 + dx;
+                        let ny = cy as i32 + dy;
+                        if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                            continue;
+                        }
+                        let (nx, ny) = (nx as usize, ny as usize);
+                        if !seen[ny][nx] && (board.at(nx, ny) == op || board.at(nx, ny) == Cell::Empty) {
+                            seen[ny][nx] = true;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+            }
+        }
+        regions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::testing::{board_state, frame};
+    use crate::types::PieceOffset;
+
+    #[test]
+    fn our_wall_splits_opponent_regions() {
+        let open = board_state(&["$...$", ".....", "....."]);
+        assert_eq!(open.opponent_region_count(&open.board), 1);
+        let walled = board_state(&["$.@.$", "..@..", "..@.."]);
+        assert_eq!(walled.opponent_region_count(&walled.board), 2);
+    }
+
+    #[test]
+    fn placement_can_split_their_regions_but_never_merge_them() {
+        let (state, offsets, _, _) = frame(&["$.@.$", "..@..", "....."], &["#", "#"]);
+        assert_eq!(state.opponent_region_count(&state.board), 1);
+        // Closing the gap under our wall cuts them in two
+        assert_eq!(state.opponent_region_count(&state.simulate_move(2, 1, &offsets)), 2);
+
+        // Our cells only ever add walls, so no placement joins the two halves again
+        let (split, offsets, tx, ty) = frame(&["$.@.$", "..@..", "..@.."], &["##"]);
+        let moves = split.find_legal_moves(&offsets, tx, ty);
+        assert!(!moves.is_empty());
+        for (x, y) in moves {
+            assert!(split.opponent_region_count(&split.simulate_move(x, y, &offsets)) >= 2);
+        }
+    }
+
+    #[test]
+    fn frontier_facing_their_side_is_exposed_and_the_pocket_side_safe() {
+        let state = board_state(&["..@....", "..@....", "@@@...$"]);
+        let reach = state.opponent_reachability_on(&state.board);
+        // (0, 2) and (1, 2) only border the sealed pocket; the wall's right face is contested
+        assert_eq!(state.classify_frontier(&state.board, &reach), (2, 3));
+
+        // Without an opponent in play every frontier cell is safe
+        let nobody = vec![vec![false; 7]; 3];
+        assert_eq!(state.classify_frontier(&state.board, &nobody), (5, 0));
+    }
+
+    #[test]
+    fn openness_prefers_leaving_a_square_pocket_over_a_corridor() {
+        // Same area, walled in: a 2x2 pocket against a 4x1 corridor
+        let square = board_state(&["@@@@", "@..@", "@..@", "@@@@"]);
+        let corridor = board_state(&["@@@@@@", "@....@", "@@@@@@"]);
+        assert_eq!(square.frontier_openness_score(&square.board), 5);
+        assert_eq!(corridor.frontier_openness_score(&corridor.board), 4);
+
+        // A bar across the top of a 2x4 pocket leaves a 2x3 block; down its side, a strip
+        let rows = ["@@@@", "@..@", "@..@", "@..@", "@..@", "@@@@"];
+        let (state, across, _, _) = frame(&rows, &["###"]);
+        let (_, down, _, _) = frame(&rows, &["#", "#", "#"]);
+        assert!(state.is_legal_move(0, 1, &across) && state.is_legal_move(1, 0, &down));
+        let block = state.frontier_openness_score(&state.simulate_move(0, 1, &across));
+        let strip = state.frontier_openness_score(&state.simulate_move(1, 0, &down));
+        assert_eq!((block, strip), (6, 5));
+    }
+
+    #[test]
+    fn frontier_skips_our_cells_and_walled_off_space() {
+        let state = board_state(&["$@...", ".@...", "@....", "....."]);
+        assert_eq!(state.opponent_frontier(), vec![(0, 1)]);
+        assert!(!state.is_opponent_frontier_cell(1, 0));
+        // Space behind our wall is not theirs to unlock
+        assert_eq!(state.frontier_unlock_value(0, 1), 0);
+    }
+
+    #[test]
+    fn frontier_ranks_the_cell_opening_more_space_first() {
+        let state = board_state(&["@....", ".....", "..$..", "..@@@", "....."]);
+        let frontier = state.opponent_frontier();
+        assert_eq!(frontier.len(), 3);
+        assert_eq!(frontier[0], (2, 1));
+        assert!(!frontier.contains(&(2, 3)));
+    }
+
+    #[test]
+    fn covering_the_only_frontier_cell_denies_the_most() {
+        let (state, offsets, tx, ty) = frame(&["$.@....", "@.@....", "@......", "@@@@..."], &["##"]);
+        assert_eq!(state.opponent_frontier(), vec![(1, 0)]);
+
+        let moves = state.find_legal_moves(&offsets, tx, ty);
+        let best = moves.iter().max_by_key(|&&(x, y)| state.frontier_denial_score(x, y, &offsets)).unwrap();
+        assert_eq!(*best, (1, 0));
+        let covering = state.frontier_denial_score(1, 0, &offsets);
+        assert!(moves.iter().filter(|&&m| m != (1, 0)).all(|&(x, y)| state.frontier_denial_score(x, y, &offsets) < covering));
+    }
+
+    #[test]
+    fn frontier_length_before_and_after_a_placement() {
+        let (state, offsets, _, _) = frame(&["@....", ".....", "....$"], &["##"]);
+        assert_eq!(state.my_frontier_length(), 1);
+        assert_eq!(state.frontier_length_delta(0, 0, &offsets), 1);
+        assert_eq!(state.my_frontier_length_on(&state.simulate_move(0, 0, &offsets)), 2);
+
+        // Filling the last gap in a wall closes a frontier cell
+        let (state, offsets, _, _) = frame(&["@@.$$", "@@@$$", "@@@$$"], &["##"]);
+        let before = state.my_frontier_length();
+        let after = state.my_frontier_length_on(&state.simulate_move(1, 0, &offsets));
+        assert_eq!((before, after), (2, 0));
+        assert_eq!(state.frontier_length_delta(1, 0, &offsets), -2);
+    }
+
+    #[test]
+    fn sealing_a_gap_removes_an_anchor_region() {
+        let (state, offsets, _, _) = frame(&["..$$$..", "@@@@@@@", "......."], &["#", "#"]);
+        assert_eq!(state.opponent_anchor_regions(&state.board), 2);
+        assert_eq!(state.anchor_region_reduction(0, 0, &offsets), 1);
+        assert_eq!(state.anchor_region_reduction(6, 0, &offsets), 1);
+        assert_eq!(state.anchor_region_reduction(3, 1, &offsets), 0);
+    }
+
+    #[test]
+    fn bar_along_their_line_takes_away_their_placements() {
+        // Their line runs down the middle of a corridor; a vertical domino fits above or below each cell
+        let rows = ["@@@@@@@@@@", "@@........", "@.$$$$$$$$", "@.........", "@@@@@@@@@@"];
+        let (state, offsets, _, _) = frame(&rows, &["########"]);
+        let domino = [PieceOffset::new(0, 0), PieceOffset::new(0, 1)];
+        assert_eq!(state.opponent_legal_move_count_on(&state.board, &domino), 16);
+
+        // Covering the corridor above x = 2..8 leaves them only (9, 1) on that side
+        assert_eq!(state.opponent_legal_move_delta(&state.simulate_move(1, 1, &offsets), &domino), 7);
+        assert_eq!(state.opponent_legal_move_delta(&state.board, &domino), 0);
+    }
+}
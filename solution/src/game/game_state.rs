@@ -1,18 +1,32 @@
-use crate::types::{Player, Cell, PieceCell, PieceOffset};
-use std::collections::VecDeque;
+use crate::types::{Player, Cell, PieceCell, PieceOffset, TieBreak, ScanOrder, CaptureRule, Strategy, OutputFormat};
+use crate::error::ParseError;
+use super::board::{Board, Grid};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+#[cfg(test)]
+thread_local! {
+    /// Distance maps built on this thread (test spy)
+    pub(crate) static DISTANCE_MAPS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
 
 /// Game state structure that holds all information about the current game state
 /// and provides methods for parsing input, calculating legal moves, and determining
 /// the optimal move using a sophisticated heuristic.
+#[derive(Clone)]
 pub struct GameState {
     /// Current player (One or Two)
     pub player: Player,
+    /// Whether `player` was set explicitly rather than left at its default
+    pub player_known: bool,
     /// Width of the game board
     pub board_width: usize,
     /// Height of the game board
     pub board_height: usize,
-    /// 2D representation of the board state
-    pub board: Vec<Vec<Cell>>,
+    /// Board state (packed 2 bits per cell with the `packed-board` feature)
+    pub board: Board,
+    /// Empty cells of `board`, kept in step by `set_cell` (see `sync_empty_cells`)
+    pub empty_cells: HashSet<(usize, usize)>,
     /// Width of the current piece
     pub piece_width: usize,
     /// Height of the current piece
@@ -31,6 +45,134 @@ pub struct GameState {
     pub blocking_weight: i32,
     /// Weight for the compactness component of the heuristic
     pub compactness_weight: i32,
+    /// Game progress at which the early (expansion) phase ends
+    pub early_phase_end: f32,
+    /// Game progress at which the late (endgame) phase starts
+    pub late_phase_start: f32,
+    /// Weight for claiming or bordering the opponent's most valuable frontier cells (0 disables)
+    pub frontier_weight: i32,
+    /// How ties between equally scored moves are broken
+    pub tie_break: TieBreak,
+    /// Weight for claiming cells the opponent needs many steps to reach (0 disables the A* search)
+    pub path_cost_weight: i32,
+    /// Weight for keeping our bounding box well filled (percent fill after the move)
+    pub fill_ratio_weight: i32,
+    /// Weight per empty neighbor of the own cell the piece overlaps (growing from the edge) (0 disables)
+    pub overlap_frontier_weight: i32,
+    /// Weight for growing our frontier early and shrinking it late (0 disables)
+    pub frontier_length_weight: i32,
+    /// Weight pulling our placements toward the mirror image of the opponent (0 disables)
+    pub mirror_weight: i32,
+    /// Weight for cells the opponent can never reach: penalized while contested, rewarded in the endgame (0 disables)
+    pub safe_cell_weight: i32,
+    /// Weight per cell between an early placement and the board center (fades out over the early phase)
+    pub centrality_weight: i32,
+    /// Weight for shrinking the number of separate spots the opponent can anchor on
+    pub anchor_region_weight: i32,
+    /// Penalty per open cell our next-turn mobility falls short of the floor (0 disables)
+    pub mobility_weight: i32,
+    /// Next-turn mobility (open cells around our territory) we try not to drop below
+    pub mobility_floor: i32,
+    /// Weight for empty cells a placement brings within our reach (0 disables)
+    pub unlock_weight: i32,
+    /// Penalty per one-cell hole a placement leaves inside our territory (0 disables)
+    pub hole_weight: i32,
+    /// Weight for opponent-reachable empty cells a placement walls off from them (0 disables)
+    pub region_capture_weight: i32,
+    /// Weight for elongated pieces growing along the open space at their anchor (0 disables)
+    pub orientation_weight: i32,
+    /// Mid-game penalty per step between a placement and the contested Voronoi border
+    pub contested_frontier_weight: i32,
+    /// Lead over the opponent (percent of their territory) at which we switch to wall building; 0 disables
+    pub wall_lead_percent: i32,
+    /// Early weight for claiming cells on or beside the shortest path between the two territories (0 disables)
+    pub seed_barrier_weight: i32,
+    /// Turns (boards seen) during which the seed barrier term applies
+    pub seed_barrier_turns: i32,
+    /// Penalty per frontier cell of ours left bordering space the opponent can reach (0 disables)
+    pub exposed_frontier_weight: i32,
+    /// Early-to-mid game, placements reaching farther than this from our centroid are heavily penalized (None: no limit)
+    pub max_reach_radius: Option<i32>,
+    /// Weight for keeping the open space next to us blocky rather than stringy (0 disables)
+    pub frontier_openness_weight: i32,
+    /// Weight per own neighbor of the own cell the piece overlaps (spending interior cells on the overlap)
+    pub interior_overlap_weight: i32,
+    /// Weight pulling placements toward our territory (0 or negative for wall-building)
+    pub connectivity_weight: i32,
+    /// Control points (game progress, multiplier) scaling the blocking term
+    pub aggression_curve: Vec<(f32, f32)>,
+    /// Positions the lookahead may visit per turn (0: no lookahead, see `adaptive_depth`)
+    pub node_budget: usize,
+    /// Extra value for claiming each cell, indexed `[y][x]` like the board (None: uniform)
+    pub zone_values: Option<Vec<Vec<i32>>>,
+    /// Cells worth a large bonus to claim (scripted objectives); see `set_key_squares`
+    pub key_squares: HashSet<(usize, usize)>,
+    /// Most legal placements scored per turn, nearest our territory kept (0: no cap)
+    pub max_candidates: usize,
+    /// Order legal anchors are found in, and so which survive thinning among equals
+    pub scan_order: ScanOrder,
+    /// Best-move score below which we consider the position likely lost
+    pub losing_score_floor: i32,
+    /// Opponent territory centroid recorded from the latest board
+    pub last_opponent_centroid: Option<(f32, f32)>,
+    /// Number of piece rows seen that were longer than the declared piece width
+    pub piece_width_mismatches: usize,
+    /// Debug builds: print why candidate placements were rejected, every turn
+    pub log_rejections: bool,
+    /// Log each turn's decision time and the slowest so far to stderr
+    pub log_timing: bool,
+    /// Wall-clock time of the latest turn's decision
+    pub last_decision_time: Option<Duration>,
+    /// Slowest decision this game
+    pub max_decision_time: Duration,
+    /// Check each board row's gutter number against the row being parsed
+    pub strict_row_numbers: bool,
+    /// Glyphs marking filled piece cells (player symbols too: pieces have no owner)
+    pub piece_filled_glyphs: Vec<char>,
+    /// Glyphs marking empty piece cells
+    pub piece_empty_glyphs: Vec<char>,
+    /// Columns of indentation stripped from every piece row
+    pub piece_indent: usize,
+    /// Largest board (and piece) width or height accepted; larger declarations
+    /// are rejected before anything is allocated
+    pub max_board_dim: usize,
+    /// Pad piece rows shorter than the piece width with empty cells (with a warning)
+    /// instead of rejecting them
+    pub lenient_piece_rows: bool,
+    /// Whether placements may capture an opponent cell
+    pub capture_rule: CaptureRule,
+    /// Own cells a placement must cover; 1 is the standard rule, and values
+    /// below 1 are treated as 1 (for engines seeding several start cells)
+    pub required_own_overlap: usize,
+    /// Move-selection strategy (the baselines ignore every scoring knob)
+    pub strategy: Strategy,
+    /// Coordinate order and y-axis direction of the moves we write
+    pub output_format: OutputFormat,
+    /// Cells the opponent claimed between the previous board and the latest one
+    pub last_opponent_move: Vec<(usize, usize)>,
+    /// Cells we gained between consecutive boards, oldest first
+    pub tempo_history: Vec<usize>,
+    /// Our territory size on the latest board, once one has been read
+    pub last_my_territory: Option<usize>,
+    /// Cells the opponent gained between consecutive boards, oldest first
+    pub opponent_growth_history: Vec<usize>,
+    /// Switch to `survival_move` once fewer empty cells than this are reachable
+    /// from our territory (0 disables)
+    pub survival_threshold: usize,
+    /// Against a detected mirroring opponent, go for the board center (see `anti_mirror_active`)
+    pub anti_mirror: bool,
+    /// Consecutive boards on which the position was point-symmetric
+    pub mirror_streak: usize,
+    /// Scale blocking by the opponent's momentum (see `opponent_growth_rate`)
+    pub momentum_blocking: bool,
+    /// Variant rule: the piece may be rotated (see `best_move_oriented`)
+    pub rotations_allowed: bool,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameState {
@@ -38,9 +180,11 @@ impl GameState {
     pub fn new() -> Self {
         GameState {
             player: Player::One, // Default, will be updated
+            player_known: false,
             board_width: 0,
             board_height: 0,
-            board: Vec::new(),
+            board: Board::empty(0, 0),
+            empty_cells: HashSet::new(),
             piece_width: 0,
             piece_height: 0,
             piece: Vec::new(),
@@ -51,26 +195,155 @@ impl GameState {
             expansion_weight: 30, // MAXIMUM: Prioritize expansion above all
             blocking_weight: 20,  // HIGH: Block opponent aggressively
             compactness_weight: -10, // SEVERE penalty: Force ultra-compact territory
+            early_phase_end: 0.35,
+            late_phase_start: 0.70,
+            frontier_weight: 0,   // Opt-in: steal the opponent's best expansion squares
+            tie_break: TieBreak::TopLeft,
+            path_cost_weight: 0,
+            fill_ratio_weight: 0,
+            overlap_frontier_weight: 0,
+            frontier_length_weight: 0,
+            mirror_weight: 0,
+            safe_cell_weight: 0,
+            centrality_weight: 0,
+            anchor_region_weight: 0,
+            mobility_weight: 0,
+            mobility_floor: 4,
+            unlock_weight: 0,
+            hole_weight: 0,
+            region_capture_weight: 0,
+            orientation_weight: 0,
+            contested_frontier_weight: 0,
+            wall_lead_percent: 0,
+            seed_barrier_weight: 0,
+            seed_barrier_turns: 8,
+            exposed_frontier_weight: 0,
+            max_reach_radius: None,
+            frontier_openness_weight: 0,
+            interior_overlap_weight: 0,
+            connectivity_weight: 10,
+            aggression_curve: vec![(0.0, 1.0), (1.0, 1.0)], // flat: phase weights alone
+            node_budget: 0,
+            max_candidates: 0,
+            scan_order: ScanOrder::RowMajor,
+            zone_values: None,
+            key_squares: HashSet::new(),
+            losing_score_floor: 100,
+            last_opponent_centroid: None,
+            piece_width_mismatches: 0,
+            log_rejections: false,
+            log_timing: false,
+            last_decision_time: None,
+            max_decision_time: Duration::ZERO,
+            strict_row_numbers: false,
+            piece_filled_glyphs: vec!['#', 'O', 'o', '@', '$'],
+            piece_empty_glyphs: vec!['.'],
+            piece_indent: 0,
+            lenient_piece_rows: false,
+            max_board_dim: 1000,
+            capture_rule: CaptureRule::Forbidden,
+            required_own_overlap: 1,
+            strategy: Strategy::Heuristic,
+            output_format: OutputFormat::default(),
+            last_opponent_move: Vec::new(),
+            tempo_history: Vec::new(),
+            last_my_territory: None,
+            opponent_growth_history: Vec::new(),
+            momentum_blocking: false,
+            anti_mirror: false,
+            survival_threshold: 0,
+            mirror_streak: 0,
+            rotations_allowed: false,
+        }
+    }
+
+    /// Forget everything about the current game, keeping the configuration
+    ///
+    /// Board, piece, player and per-game history go back to their initial
+    /// state; weights, parsing options and strategy are kept.
+    pub fn reset_for_new_game(&mut self) {
+        let fresh = GameState::new();
+        self.player = fresh.player;
+        self.player_known = fresh.player_known;
+        self.my_symbols = fresh.my_symbols;
+        self.opponent_symbols = fresh.opponent_symbols;
+        self.board_width = fresh.board_width;
+        self.board_height = fresh.board_height;
+        self.board = fresh.board;
+        self.empty_cells = fresh.empty_cells;
+        self.piece_width = fresh.piece_width;
+        self.piece_height = fresh.piece_height;
+        self.piece = fresh.piece;
+        self.last_opponent_centroid = fresh.last_opponent_centroid;
+        self.piece_width_mismatches = fresh.piece_width_mismatches;
+        self.last_decision_time = fresh.last_decision_time;
+        self.max_decision_time = fresh.max_decision_time;
+        self.last_opponent_move = fresh.last_opponent_move;
+        self.tempo_history = fresh.tempo_history;
+        self.last_my_territory = fresh.last_my_territory;
+        self.opponent_growth_history = fresh.opponent_growth_history;
+        self.mirror_streak = fresh.mirror_streak;
+    }
+
+    /// Bounds-checked board lookup; `None` for coordinates off the board
+    pub fn board_get(&self, x: i32, y: i32) -> Option<Cell> {
+        let (x, y) = to_board_index(x, y, self.board_width, self.board_height)?;
+        self.board.get_checked(x, y)
+    }
+
+    /// Overwrite one cell, keeping `empty_cells` in step
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: Cell) {
+        self.board.put(x, y, cell);
+        if cell == Cell::Empty {
+            self.empty_cells.insert((x, y));
+        } else {
+            self.empty_cells.remove(&(x, y));
+        }
+    }
+
+    /// Rebuild `empty_cells` after `board` was replaced wholesale
+    pub fn sync_empty_cells(&mut self) {
+        self.empty_cells.clear();
+        for (x, y, cell) in self.board.cells() {
+            if cell == Cell::Empty {
+                self.empty_cells.insert((x, y));
+            }
+        }
+    }
+
+    /// Move as written to the engine, per `output_format`
+    pub fn format_move(&self, x: i32, y: i32) -> String {
+        let y = if self.output_format.flip_y { self.board_height as i32 - 1 - y } else { y };
+        if self.output_format.swap_xy {
+            format!("{} {}", y, x)
+        } else {
+            format!("{} {}", x, y)
         }
     }
 
+    /// Board cell belonging to us
+    pub fn my_cell(&self) -> Cell {
+        if self.player == Player::One { Cell::Player1 } else { Cell::Player2 }
+    }
+
+    /// Board cell belonging to the opponent
+    pub fn opponent_cell(&self) -> Cell {
+        if self.player == Player::One { Cell::Player2 } else { Cell::Player1 }
+    }
+
     /// Parse player information from the input line
     pub fn parse_player(&mut self, line: &str) {
         // Extract player number from "$$$ exec p<number> : [<path>]"
         if let Some(player_char) = line.chars().nth("$$$ exec p".len()) {
             match player_char {
                 '1' => {
-                    self.player = Player::One;
-                    self.my_symbols = ('@', 'a');
-                    self.opponent_symbols = ('$', 's');
+                    self.set_player(Player::One);
                     // Minimal logging
                     #[cfg(debug_assertions)]
                     eprintln!("I am Player 1");
                 },
                 '2' => {
-                    self.player = Player::Two;
-                    self.my_symbols = ('$', 's');
-                    self.opponent_symbols = ('@', 'a');
+                    self.set_player(Player::Two);
                     // Minimal logging
                     #[cfg(debug_assertions)]
                     eprintln!("I am Player 2");
@@ -80,19 +353,45 @@ impl GameState {
         }
     }
 
+    /// Take on a player and its symbols
+    pub fn set_player(&mut self, player: Player) {
+        self.player = player;
+        (self.my_symbols, self.opponent_symbols) = match player {
+            Player::One => (('@', 'a'), ('$', 's')),
+            Player::Two => (('$', 's'), ('@', 'a')),
+        };
+        self.player_known = true;
+    }
+
+    /// Guess our player from a board when no `$$$ exec` line named it
+    ///
+    /// Player 1 moves first, so when we are asked to move while `@` already
+    /// holds more cells than `$`, player 1 has played ahead of us and we are
+    /// player 2. Otherwise the board is still symmetric and we are player 1.
+    pub fn infer_player_from_board(&mut self) {
+        let count = |target: Cell| self.board.cells().filter(|&(_, _, cell)| cell == target).count();
+        let player = if count(Cell::Player1) > count(Cell::Player2) { Player::Two } else { Player::One };
+        eprintln!("Warning: no player line before the board; assuming {:?} from the board", player);
+        self.set_player(player);
+    }
+
     /// Parse board dimensions and initialize the board
-    pub fn parse_board_dimensions(&mut self, line: &str) -> Result<(), String> {
+    pub fn parse_board_dimensions(&mut self, line: &str) -> Result<(), ParseError> {
         // Extract dimensions from "Anfield <W> <H>:"
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() < 3 {
-            return Err(format!("Invalid board dimensions line: {}", line));
+            return Err(ParseError::BadDimensions(line.to_string()));
         }
 
-        self.board_width = parts[1].parse::<usize>().map_err(|e| e.to_string())?;
-        self.board_height = parts[2].trim_end_matches(':').parse::<usize>().map_err(|e| e.to_string())?;
+        let width = parts[1].parse::<usize>().map_err(|_| ParseError::BadDimensions(line.to_string()))?;
+        let height = parts[2].trim_end_matches(':').parse::<usize>().map_err(|_| ParseError::BadDimensions(line.to_string()))?;
+        self.check_dimensions(width, height)?;
+        self.board_width = width;
+        self.board_height = height;
         
         // Initialize the board with empty cells
-        self.board = vec![vec![Cell::Empty; self.board_width]; self.board_height];
+        self.board = Board::empty(self.board_width, self.board_height);
+        self.sync_empty_cells();
         
         // Minimal logging
         #[cfg(debug_assertions)]
@@ -100,32 +399,59 @@ impl GameState {
         Ok(())
     }
 
-    pub fn parse_board_row(&mut self, line: &str, row_idx: usize) -> Result<(), String> {
-        let line_content = if line.len() >= 4 { &line[4..] } else { line };
+    /// Reject declared dimensions over `max_board_dim` (a malformed header
+    /// would otherwise try to allocate an enormous grid)
+    fn check_dimensions(&self, width: usize, height: usize) -> Result<(), ParseError> {
+        if width > self.max_board_dim || height > self.max_board_dim {
+            return Err(ParseError::DimensionsTooLarge { width, height, max: self.max_board_dim });
+        }
+        Ok(())
+    }
+
+    /// Parse a board row (row-number gutter, then the cells)
+    ///
+    /// With `strict_row_numbers`, a row whose gutter is not `row_idx` is rejected
+    /// instead of silently overwriting the wrong row.
+    pub fn parse_board_row(&mut self, line: &str, row_idx: usize) -> Result<(), ParseError> {
+        let (gutter, line_content) = split_gutter(line);
+        if self.strict_row_numbers && gutter.parse::<usize>().ok() != Some(row_idx) {
+            return Err(ParseError::RowNumberMismatch { expected: row_idx, gutter: gutter.to_string() });
+        }
         if line_content.len() < self.board_width {
-            return Err(format!("Board row too short: {}", line_content));
+            return Err(ParseError::RowTooShort { expected: self.board_width, got: line_content.len() });
         }
         for (col_idx, ch) in line_content.chars().take(self.board_width).enumerate() {
-            self.board[row_idx][col_idx] = match ch {
+            let cell = match ch {
                 '.' => Cell::Empty,
                 '@' | 'a' => Cell::Player1,
                 '$' | 's' => Cell::Player2,
-                _ => return Err(format!("Unknown board cell: {}", ch)),
+                _ => return Err(ParseError::UnknownBoardGlyph(ch)),
             };
+            self.set_cell(col_idx, row_idx, cell);
         }
         Ok(())
     }
 
     /// Parse piece dimensions and initialize the piece
-    pub fn parse_piece_dimensions(&mut self, line: &str) -> Result<(), String> {
+    pub fn parse_piece_dimensions(&mut self, line: &str) -> Result<(), ParseError> {
         // Extract dimensions from "Piece <w> <h>:"
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() < 3 {
-            return Err(format!("Invalid piece dimensions line: {}", line));
+            return Err(ParseError::BadDimensions(line.to_string()));
         }
 
-        self.piece_width = parts[1].parse::<usize>().map_err(|e| e.to_string())?;
-        self.piece_height = parts[2].trim_end_matches(':').parse::<usize>().map_err(|e| e.to_string())?;
+        let width = parts[1].parse::<usize>().map_err(|_| ParseError::BadDimensions(line.to_string()))?;
+        let height = parts[2].trim_end_matches(':').parse::<usize>().map_err(|_| ParseError::BadDimensions(line.to_string()))?;
+        self.check_dimensions(width, height)?;
+        #[cfg(feature = "compact-offsets")]
+        {
+            let max = crate::types::CompactOffset::MAX_PIECE_DIM;
+            if width > max || height > max {
+                return Err(ParseError::DimensionsTooLarge { width, height, max });
+            }
+        }
+        self.piece_width = width;
+        self.piece_height = height;
         
         // Initialize the piece with empty cells
         self.piece = vec![vec![PieceCell::Empty; self.piece_width]; self.piece_height];
@@ -137,26 +463,137 @@ impl GameState {
     }
 
     /// Parse a piece row
-    pub fn parse_piece_row(&mut self, line: &str, row_idx: usize) -> Result<(), String> {
+    ///
+    /// Strips `piece_indent` columns first; any leading whitespace left after
+    /// that is treated as indentation too, since piece glyphs never include it.
+    /// A short row is an error unless `lenient_piece_rows` is set.
+    pub fn parse_piece_row(&mut self, line: &str, row_idx: usize) -> Result<(), ParseError> {
+        let indent = line.char_indices().nth(self.piece_indent).map_or(line.len(), |(i, _)| i);
+        let line = line[indent..].trim_start();
+
         // Ensure the line has enough characters
         if line.len() < self.piece_width {
-            return Err(format!("Piece row too short: {}", line));
+            if !self.lenient_piece_rows {
+                return Err(ParseError::RowTooShort { expected: self.piece_width, got: line.len() });
+            }
+            eprintln!("Warning: piece row {} has {} cells, padding to {}", row_idx, line.len(), self.piece_width);
+            for cell in self.piece[row_idx].iter_mut().skip(line.len()) {
+                *cell = PieceCell::Empty;
+            }
+        }
+
+        // Anything past the declared width must be padding, otherwise the width is out of sync
+        if line.len() > self.piece_width {
+            self.piece_width_mismatches += 1;
+            if let Some(ch) = line.chars().skip(self.piece_width).find(|&c| !self.piece_empty_glyphs.contains(&c) && !c.is_whitespace()) {
+                return Err(ParseError::PieceWidthMismatch { width: self.piece_width, glyph: ch });
+            }
         }
 
         // Parse each character in the row
         for (col_idx, ch) in line.chars().take(self.piece_width).enumerate() {
-            self.piece[row_idx][col_idx] = match ch {
-                '.' => PieceCell::Empty,
-                '#' | 'O' | 'o' => PieceCell::Filled,
-                _ => return Err(format!("Unknown piece cell: {}", ch)),
+            self.piece[row_idx][col_idx] = if self.piece_empty_glyphs.contains(&ch) {
+                PieceCell::Empty
+            } else if self.piece_filled_glyphs.contains(&ch) {
+                PieceCell::Filled
+            } else {
+                return Err(ParseError::UnknownPieceGlyph(ch));
             };
         }
 
         Ok(())
     }
 
+    /// Install a per-cell value overlay that scoring adds for every claimed cell
+    ///
+    /// The overlay must have the current board's dimensions.
+    pub fn set_zone_values(&mut self, zones: Vec<Vec<i32>>) -> Result<(), ParseError> {
+        let height = zones.len();
+        let width = zones.first().map_or(0, |row| row.len());
+        if height != self.board_height || zones.iter().any(|row| row.len() != self.board_width) {
+            return Err(ParseError::ZoneSizeMismatch {
+                expected: (self.board_width, self.board_height),
+                got: (width, height),
+            });
+        }
+        self.zone_values = Some(zones);
+        Ok(())
+    }
+
+    /// Mark (x, y) cells as objectives: claiming one outweighs any ordinary move
+    ///
+    /// Replaces any earlier list; an empty list turns the bonus off.
+    pub fn set_key_squares(&mut self, cells: Vec<(usize, usize)>) {
+        self.key_squares = cells.into_iter().collect();
+    }
+
+    /// Replace the glyphs `parse_piece_row` accepts for filled and empty cells
+    ///
+    /// Fails without changing anything if a glyph appears in both sets.
+    pub fn set_piece_glyphs(&mut self, filled: &[char], empty: &[char]) -> Result<(), ParseError> {
+        if let Some(&ch) = filled.iter().find(|ch| empty.contains(ch)) {
+            return Err(ParseError::AmbiguousPieceGlyph(ch));
+        }
+        self.piece_filled_glyphs = filled.to_vec();
+        self.piece_empty_glyphs = empty.to_vec();
+        Ok(())
+    }
+
+    /// Parse a whole frame (optional player line, `Anfield` block, `Piece` block)
+    ///
+    /// Runs the same steps as a turn read from the engine and returns the
+    /// trimmed offsets and trim offset, ready for `best_move`. Lines before the
+    /// `Anfield` line other than the player line are ignored.
+    pub fn parse_frame(&mut self, frame: &str) -> Result<(Vec<PieceOffset>, i32, i32), ParseError> {
+        let mut lines = frame.lines().skip_while(|line| !line.starts_with("Anfield ") && !line.starts_with("$$$ exec p"));
+        let mut board_line = lines.next().ok_or(ParseError::IncompleteFrame("Anfield line"))?;
+        if board_line.starts_with("$$$ exec p") {
+            self.parse_player(board_line);
+            board_line = lines.next().ok_or(ParseError::IncompleteFrame("Anfield line"))?;
+        }
+        self.parse_board_dimensions(board_line)?;
+        lines.next().ok_or(ParseError::IncompleteFrame("column header"))?;
+        for row_idx in 0..self.board_height {
+            self.parse_board_row(lines.next().ok_or(ParseError::IncompleteFrame("board rows"))?, row_idx)?;
+        }
+        if !self.player_known {
+            self.infer_player_from_board();
+        }
+
+        self.parse_piece_dimensions(lines.next().ok_or(ParseError::IncompleteFrame("Piece line"))?)?;
+        for row_idx in 0..self.piece_height {
+            self.parse_piece_row(lines.next().ok_or(ParseError::IncompleteFrame("piece rows"))?, row_idx)?;
+        }
+        Ok(self.trim_piece())
+    }
+
     /// Trim the piece to its minimal bounding box and PRECISELY return offsets
+    ///
+    /// Rewrites the stored piece and its dimensions to the trimmed box; use
+    /// `compute_piece_offsets` to get the same offsets without that side effect.
     pub fn trim_piece(&mut self) -> (Vec<PieceOffset>, i32, i32) {
+        let (offsets, off_x, off_y, new_w, new_h) = self.compute_piece_offsets();
+        if offsets.is_empty() {
+            return (offsets, 0, 0);
+        }
+
+        // Update dimensions to the TRIMMED box
+        let (min_col, min_row) = (off_x as usize, off_y as usize);
+        self.piece = self.piece[min_row..min_row + new_h]
+            .iter()
+            .map(|row| row[min_col..min_col + new_w].to_vec())
+            .collect();
+        self.piece_width = new_w;
+        self.piece_height = new_h;
+
+        (offsets, off_x, off_y)
+    }
+
+    /// Offsets of the filled piece cells within their minimal bounding box
+    ///
+    /// Returns the offsets, the trim offset (x, y) mapping trimmed → original,
+    /// and the trimmed width and height. Leaves the piece itself untouched.
+    pub fn compute_piece_offsets(&self) -> (Vec<PieceOffset>, i32, i32, usize, usize) {
         // Find bounds of filled cells within the original piece grid
         let mut min_row = self.piece_height;
         let mut max_row = 0;
@@ -175,47 +612,46 @@ impl GameState {
         }
         // no filled cells? (shouldn't happen) — return empty
         if min_row > max_row || min_col > max_col {
-            return (Vec::new(), 0, 0);
+            return (Vec::new(), 0, 0, 0, 0);
         }
 
         let new_h = max_row - min_row + 1;
         let new_w = max_col - min_col + 1;
-        let mut new_piece = vec![vec![PieceCell::Empty; new_w]; new_h];
         let mut offsets = Vec::new();
-
         for r in 0..new_h {
             for c in 0..new_w {
-                let cell = self.piece[min_row + r][min_col + c];
-                new_piece[r][c] = cell;
-                if cell == PieceCell::Filled {
-                    offsets.push(PieceOffset { dx: c as i32, dy: r as i32 });
+                if self.piece[min_row + r][min_col + c] == PieceCell::Filled {
+                    offsets.push(PieceOffset::new(c as i32, r as i32));
                 }
             }
         }
 
-        // Update dimensions to the TRIMMED box
-        self.piece = new_piece;
-        self.piece_width = new_w;
-        self.piece_height = new_h;
-
         // Return offsets to map trimmed → original
-        (offsets, min_col as i32, min_row as i32)
+        (offsets, min_col as i32, min_row as i32, new_w, new_h)
     }
 
     /// Calculate distance map from opponent cells
     pub fn calculate_distance_map(&self) -> Vec<Vec<i32>> {
-        let mut distance_map = vec![vec![-1; self.board_width]; self.board_height];
+        let flat = self.calculate_distance_map_flat();
+        (0..self.board_height)
+            .map(|y| flat[y * self.board_width..(y + 1) * self.board_width].to_vec())
+            .collect()
+    }
+
+    /// `calculate_distance_map` in one row-major buffer, `board_width` per row
+    pub fn calculate_distance_map_flat(&self) -> Vec<i32> {
+        #[cfg(test)]
+        DISTANCE_MAPS.with(|maps| maps.set(maps.get() + 1));
+        let width = self.board_width;
+        let mut distance_map = vec![-1; width * self.board_height];
         let mut queue = VecDeque::new();
         
         // Initialize queue with opponent cells
-        for y in 0..self.board_height {
-            for x in 0..self.board_width {
-                let cell = self.board[y][x];
-                if (self.player == Player::One && cell == Cell::Player2) ||
-                   (self.player == Player::Two && cell == Cell::Player1) {
-                    distance_map[y][x] = 0;
-                    queue.push_back((x, y));
-                }
+        for (x, y, cell) in self.board.cells() {
+            if (self.player == Player::One && cell == Cell::Player2) ||
+               (self.player == Player::Two && cell == Cell::Player1) {
+                distance_map[y * width + x] = 0;
+                queue.push_back((x, y));
             }
         }
         
@@ -223,7 +659,7 @@ impl GameState {
         let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
         
         while let Some((x, y)) = queue.pop_front() {
-            let current_dist = distance_map[y][x];
+            let current_dist = distance_map[y * width + x];
             
             for (dx, dy) in directions.iter() {
                 let nx = x as i32 + dx;
@@ -234,8 +670,8 @@ impl GameState {
                     let nx = nx as usize;
                     let ny = ny as usize;
                     
-                    if distance_map[ny][nx] == -1 {
-                        distance_map[ny][nx] = current_dist + 1;
+                    if distance_map[ny * width + nx] == -1 {
+                        distance_map[ny * width + nx] = current_dist + 1;
                         queue.push_back((nx, ny));
                     }
                 }
@@ -245,13 +681,47 @@ impl GameState {
         distance_map
     }
 
+    /// Bring a distance map up to date after the opponent claimed more cells
+    ///
+    /// Opponent cells only ever get added, so distances can only shrink: the
+    /// new cells are seeded at 0 and the BFS spreads only while it improves on
+    /// `prev`. Gives the same map as `calculate_distance_map` on the new board.
+    /// (A capture that removes opponent cells needs a full recompute.)
+    pub fn update_distance_map(&self, prev: &mut [Vec<i32>], new_opponent_cells: &[(usize, usize)]) {
+        let mut queue = VecDeque::new();
+        for &(x, y) in new_opponent_cells {
+            if prev[y][x] != 0 {
+                prev[y][x] = 0;
+                queue.push_back((x, y));
+            }
+        }
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some((x, y)) = queue.pop_front() {
+            let next_dist = prev[y][x] + 1;
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                // -1 marks cells no opponent cell could reach before
+                if prev[ny][nx] == -1 || prev[ny][nx] > next_dist {
+                    prev[ny][nx] = next_dist;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
     /// Debug function to print board section around our territory
     pub fn debug_print_board_section(&self) {
         eprintln!("DEBUG: Board section (showing first 10x10):");
         for y in 0..std::cmp::min(10, self.board_height) {
             eprint!("  ");
             for x in 0..std::cmp::min(10, self.board_width) {
-                let cell = match self.board[y][x] {
+                let cell = match self.board.at(x, y) {
                     Cell::Empty => '.',
                     Cell::Player1 => '@',
                     Cell::Player2 => '$',
@@ -262,3 +732,280 @@ impl GameState {
         }
     }
 }
+
+/// Board indices of a signed coordinate, or `None` if it is off a `w`×`h` board
+///
+/// Use this instead of `as usize` wherever a coordinate may be negative (an
+/// anchor plus an offset, a neighbor): a bare cast wraps to a huge index.
+pub fn to_board_index(x: i32, y: i32, w: usize, h: usize) -> Option<(usize, usize)> {
+    if x < 0 || y < 0 || x as usize >= w || y as usize >= h {
+        return None;
+    }
+    Some((x as usize, y as usize))
+}
+
+/// Split a board row into its row-number gutter and the cells after it
+///
+/// The gutter is any run of digits and whitespace (spaces or tabs) before the
+/// first cell glyph: the engine zero-pads to three digits and a space, but
+/// wider, space-padded or tab-separated numbers work too. Cell glyphs are never
+/// digits or whitespace, so the split is unambiguous.
+fn split_gutter(line: &str) -> (&str, &str) {
+    let cells = line.trim_start_matches(|c: char| c.is_ascii_digit() || c.is_whitespace());
+    (line[..line.len() - cells.len()].trim(), cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_piece_is_rejected_before_it_is_stored() {
+        let mut state = GameState::new();
+        state.max_board_dim = 50;
+        state.parse_piece_dimensions("Piece 2 1:").unwrap();
+        let result = state.parse_piece_dimensions("Piece 60 1:");
+        assert!(matches!(result, Err(ParseError::DimensionsTooLarge { width: 60, max: 50, .. })));
+        assert_eq!((state.piece_width, state.piece_height), (2, 1));
+    }
+
+    #[test]
+    fn absurd_board_dimensions_fail_cleanly() {
+        let mut state = GameState::new();
+        state.parse_board_dimensions("Anfield 5 3:").unwrap();
+        let result = state.parse_board_dimensions("Anfield 999999 999999:");
+        assert_eq!(result, Err(ParseError::DimensionsTooLarge { width: 999999, height: 999999, max: 1000 }));
+        assert_eq!((state.board_width, state.board_height), (5, 3));
+
+        // Too large to even be a number
+        let huge = "Anfield 99999999999999999999999 3:";
+        assert_eq!(state.parse_board_dimensions(huge), Err(ParseError::BadDimensions(huge.to_string())));
+        let frame = "$$$ exec p1 : [bot]\nAnfield 1001 2:\n";
+        assert!(matches!(GameState::new().parse_frame(frame), Err(ParseError::DimensionsTooLarge { width: 1001, .. })));
+    }
+
+    #[cfg(feature = "compact-offsets")]
+    #[test]
+    fn oversized_compact_piece_is_rejected_before_it_is_stored() {
+        let mut state = GameState::new();
+        state.parse_piece_dimensions("Piece 2 1:").unwrap();
+        let result = state.parse_piece_dimensions("Piece 200 1:");
+        assert!(matches!(result, Err(ParseError::DimensionsTooLarge { width: 200, .. })));
+        assert_eq!((state.piece_width, state.piece_height), (2, 1));
+    }
+
+    #[test]
+    fn stray_filled_cell_past_the_piece_width_is_rejected() {
+        let mut state = GameState::new();
+        state.parse_piece_dimensions("Piece 3 2:").unwrap();
+        assert_eq!(state.parse_piece_row(".#...#", 0), Err(ParseError::PieceWidthMismatch { width: 3, glyph: '#' }));
+        // Padding past the width is tolerated but still counted
+        assert_eq!(state.parse_piece_row(".#....", 1), Ok(()));
+        assert_eq!(state.piece_width_mismatches, 2);
+        assert_eq!(state.piece[1], vec![PieceCell::Empty, PieceCell::Filled, PieceCell::Empty]);
+    }
+
+    #[test]
+    fn malformed_input_reports_specific_errors() {
+        let mut state = GameState::new();
+        assert_eq!(state.parse_board_dimensions("Anfield 5"), Err(ParseError::BadDimensions("Anfield 5".to_string())));
+        assert_eq!(state.parse_board_dimensions("Anfield x 3:"), Err(ParseError::BadDimensions("Anfield x 3:".to_string())));
+        state.parse_board_dimensions("Anfield 5 3:").unwrap();
+        assert_eq!(state.parse_board_row("000 ..@", 0), Err(ParseError::RowTooShort { expected: 5, got: 3 }));
+        assert_eq!(state.parse_board_row("000 ..#..", 0), Err(ParseError::UnknownBoardGlyph('#')));
+
+        state.parse_piece_dimensions("Piece 2 1:").unwrap();
+        assert_eq!(state.parse_piece_row("#", 0), Err(ParseError::RowTooShort { expected: 2, got: 1 }));
+        assert_eq!(state.parse_piece_row("#x", 0), Err(ParseError::UnknownPieceGlyph('x')));
+    }
+
+    fn parsed_piece(rows: &[&str]) -> GameState {
+        let mut state = GameState::new();
+        state.parse_piece_dimensions(&format!("Piece {} {}:", rows[0].len(), rows.len())).unwrap();
+        for (i, row) in rows.iter().enumerate() {
+            state.parse_piece_row(row, i).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn computing_offsets_leaves_the_piece_untouched() {
+        let state = parsed_piece(&["....", "..#.", ".##.", "...."]);
+        let before = (state.piece.clone(), state.piece_width, state.piece_height);
+        let (offsets, off_x, off_y, width, height) = state.compute_piece_offsets();
+        assert_eq!(offsets, vec![PieceOffset::new(1, 0), PieceOffset::new(0, 1), PieceOffset::new(1, 1)]);
+        assert_eq!((off_x, off_y, width, height), (1, 1, 2, 2));
+        assert_eq!((state.piece.clone(), state.piece_width, state.piece_height), before);
+
+        // Trimming gives the same offsets but rewrites the stored piece
+        let mut trimmed = state.clone();
+        assert_eq!(trimmed.trim_piece(), (offsets, 1, 1));
+        assert_eq!((trimmed.piece_width, trimmed.piece_height), (2, 2));
+    }
+
+    #[test]
+    fn indented_piece_block_is_read_past_its_indent() {
+        let frame = "$$$ exec p1 : [bot]\nAnfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\n\
+                     Piece 3 2:\n    .##\n    ##.\n";
+        let mut state = GameState::new();
+        let (offsets, off_x, off_y) = state.parse_frame(frame).unwrap();
+        assert_eq!(offsets, vec![PieceOffset::new(1, 0), PieceOffset::new(2, 0), PieceOffset::new(0, 1), PieceOffset::new(1, 1)]);
+        assert_eq!((off_x, off_y), (0, 0));
+
+        // A fixed indent strips exactly that many columns, whatever they hold
+        let mut state = GameState::new();
+        state.piece_indent = 2;
+        state.parse_piece_dimensions("Piece 2 1:").unwrap();
+        state.parse_piece_row("0|#.", 0).unwrap();
+        assert_eq!(state.piece[0], vec![PieceCell::Filled, PieceCell::Empty]);
+    }
+
+    #[test]
+    fn custom_filled_glyph_is_read_as_a_piece_cell() {
+        let mut state = GameState::new();
+        state.set_piece_glyphs(&['X'], &['.']).unwrap();
+        state.parse_piece_dimensions("Piece 3 2:").unwrap();
+        state.parse_piece_row(".X.", 0).unwrap();
+        state.parse_piece_row("XX.", 1).unwrap();
+        assert_eq!(state.compute_piece_offsets().0, vec![PieceOffset::new(1, 0), PieceOffset::new(0, 1), PieceOffset::new(1, 1)]);
+
+        // The default glyphs no longer count, and a glyph in both sets is refused
+        assert_eq!(state.parse_piece_row("#..", 0), Err(ParseError::UnknownPieceGlyph('#')));
+        assert_eq!(state.set_piece_glyphs(&['X'], &['X']), Err(ParseError::AmbiguousPieceGlyph('X')));
+        assert_eq!(state.piece_filled_glyphs, vec!['X']);
+    }
+
+    #[test]
+    fn strict_mode_rejects_a_mislabeled_row() {
+        let frame = "$$$ exec p1 : [bot]\nAnfield 3 3:\n    012\n000 @..\n002 ...\n001 ..$\nPiece 1 1:\n#\n";
+        let mut state = GameState::new();
+        state.strict_row_numbers = true;
+        assert_eq!(state.parse_frame(frame), Err(ParseError::RowNumberMismatch { expected: 1, gutter: "002".to_string() }));
+
+        // Lenient parsing takes the rows in order, whatever their labels
+        let mut state = GameState::new();
+        assert!(state.parse_frame(frame).is_ok());
+        assert_eq!(state.board.at(2, 2), Cell::Player2);
+    }
+
+    #[test]
+    fn negative_coordinates_map_to_no_board_index() {
+        assert_eq!(to_board_index(-1, 0, 5, 3), None);
+        assert_eq!(to_board_index(0, -1, 5, 3), None);
+        assert_eq!(to_board_index(i32::MIN, i32::MIN, 5, 3), None);
+        assert_eq!(to_board_index(5, 0, 5, 3), None);
+        assert_eq!(to_board_index(4, 2, 5, 3), Some((4, 2)));
+
+        // Lookups and placements off the top-left edge are refused, not wrapped
+        let state = crate::game::testing::board_state(&["@....", ".....", "....$"]);
+        assert_eq!(state.board_get(-1, 0), None);
+        let diagonal = [PieceOffset::new(0, 0), PieceOffset::new(1, 1)];
+        assert!(!state.is_legal_move(-1, -1, &diagonal));
+        // Only the on-board cell of a half-off placement is drawn
+        let after = state.simulate_move(-1, 0, &diagonal);
+        assert_eq!((after.at(0, 1), after.at(4, 0)), (Cell::Player1, Cell::Empty));
+    }
+
+    #[test]
+    fn lenient_mode_pads_a_short_piece_row() {
+        let mut state = GameState::new();
+        state.parse_piece_dimensions("Piece 4 3:").unwrap();
+        assert_eq!(state.parse_piece_row(".#", 1), Err(ParseError::RowTooShort { expected: 4, got: 2 }));
+
+        state.lenient_piece_rows = true;
+        state.parse_piece_row("..#.", 0).unwrap();
+        state.parse_piece_row(".#", 1).unwrap();
+        state.parse_piece_row(".##.", 2).unwrap();
+        let (offsets, off_x, off_y, width, height) = state.compute_piece_offsets();
+        assert_eq!(offsets, vec![PieceOffset::new(1, 0), PieceOffset::new(0, 1), PieceOffset::new(0, 2), PieceOffset::new(1, 2)]);
+        assert_eq!((off_x, off_y, width, height), (1, 0, 2, 3));
+    }
+
+    #[test]
+    fn whole_frame_parses_straight_to_a_move() {
+        let frame = "$$$ exec p2 : [bot]\nAnfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\nPiece 3 2:\n...\n.##\n";
+        let mut state = GameState::new();
+        let (offsets, tx, ty) = state.parse_frame(frame).unwrap();
+        assert_eq!(state.player, Player::Two);
+        assert_eq!((offsets.len(), tx, ty), (2, 1, 1));
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 1)));
+
+        // A frame cut off before its piece says what is missing
+        let cut = &frame[..frame.find("Piece").unwrap()];
+        assert_eq!(GameState::new().parse_frame(cut), Err(ParseError::IncompleteFrame("Piece line")));
+    }
+
+    #[test]
+    fn tab_delimited_gutters_strip_to_the_cells() {
+        assert_eq!(split_gutter("000\t@..$"), ("000", "@..$"));
+        assert_eq!(split_gutter("  1 \t\t..@."), ("1", "..@."));
+
+        let frame = "$$$ exec p1 : [bot]\nAnfield 4 3:\n    0123\n000\t@...\n001\t....\n002 \t...$\nPiece 1 1:\n#\n";
+        let mut state = GameState::new();
+        state.strict_row_numbers = true;
+        state.parse_frame(frame).unwrap();
+        assert_eq!(state.board.at(0, 0), Cell::Player1);
+        assert_eq!(state.board.at(3, 2), Cell::Player2);
+        assert_eq!(state.count_total_empty_cells(), 10);
+    }
+
+    #[test]
+    fn large_board_survives_a_render_and_parse_round_trip() {
+        let rows = crate::game::testing::random_rows(3, 120, 120, 50);
+        let state = crate::game::testing::board_state(&rows);
+        let rendered = state.render_board();
+
+        let mut parsed = GameState::new();
+        parsed.parse_frame(&format!("$$$ exec p1 : [bot]\n{}Piece 1 1:\n#\n", rendered)).unwrap();
+        assert_eq!(parsed.board, state.board);
+        assert_eq!(parsed.render_board(), rendered);
+
+        // Wider and unpadded gutters strip the same way
+        let gutters: [fn(usize) -> String; 3] = [|y| format!("{:04} ", y), |y| format!("{} ", y), |y| format!("{:>5}\t", y)];
+        for gutter in &gutters {
+            let mut reparsed = GameState::new();
+            reparsed.parse_board_dimensions("Anfield 120 120:").unwrap();
+            for (y, row) in rows.iter().enumerate() {
+                reparsed.parse_board_row(&format!("{}{}", gutter(y), row), y).unwrap();
+            }
+            assert_eq!(reparsed.board, state.board);
+        }
+    }
+
+    #[test]
+    fn incremental_distance_map_matches_a_full_recompute() {
+        let mut rng = 17u64;
+        let mut next = |bound: usize| {
+            rng = rng.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (rng >> 33) as usize % bound
+        };
+        for seed in 0..8 {
+            // Sparse boards: some start with no opponent cells at all
+            let mut state = crate::game::testing::board_state(&crate::game::testing::random_rows(seed, 17, 11, 96));
+            let mut map = state.calculate_distance_map();
+            for _ in 0..12 {
+                let added: Vec<(usize, usize)> = (0..1 + next(3))
+                    .map(|_| (next(17), next(11)))
+                    .filter(|&(x, y)| state.board.at(x, y) == Cell::Empty)
+                    .collect();
+                let op = state.opponent_cell();
+                for &(x, y) in &added {
+                    state.set_cell(x, y, op);
+                }
+                state.update_distance_map(&mut map, &added);
+                assert_eq!(map, state.calculate_distance_map(), "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn piece_drawn_with_player_glyphs_parses() {
+        let frame = "$$$ exec p2 : [bot]\nAnfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\n\
+                     Piece 3 2:\n.@@\n@@.\n";
+        let mut state = GameState::new();
+        let (offsets, _, _) = state.parse_frame(frame).unwrap();
+        assert_eq!(offsets, vec![PieceOffset::new(1, 0), PieceOffset::new(2, 0), PieceOffset::new(0, 1), PieceOffset::new(1, 1)]);
+        // `$` reads the same, whichever side we play
+        assert_eq!(parsed_piece(&["$.", "$$"]).compute_piece_offsets().0, vec![PieceOffset::new(0, 0), PieceOffset::new(0, 1), PieceOffset::new(1, 1)]);
+    }
+}
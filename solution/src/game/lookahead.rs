@@ -0,0 +1,114 @@
+use crate::types::{Cell, PieceOffset};
+use super::game_state::GameState;
+use super::board::{Board, Grid};
+
+/// Score charged per cell of net gain the opponent can answer a move with
+pub const LOOKAHEAD_CELL_VALUE: i32 = 100;
+
+impl GameState {
+    /// Static score of a (trimmed) placement less the opponent's best answer
+    ///
+    /// With `depth` 0 the score is returned as is. Otherwise the current piece
+    /// stands in for every later piece and `depth` further plies are searched:
+    /// each side's value is the cells it claims minus the best answer to it.
+    pub fn lookahead_score(&self, x: i32, y: i32, piece_offsets: &[PieceOffset], score: i32, depth: u8) -> i32 {
+        if depth == 0 {
+            return score;
+        }
+        let mut after = self.board.clone();
+        place_on(&mut after, x, y, piece_offsets, self.my_cell());
+        let reply = self.best_claim_margin(&after, self.opponent_cell(), piece_offsets, depth - 1);
+        score.saturating_sub(reply.saturating_mul(LOOKAHEAD_CELL_VALUE))
+    }
+
+    /// Best net cells `mover` can gain on a board, looking `depth` replies ahead
+    ///
+    /// Negamax over claimed-cell counts under the standard rules (exactly one
+    /// own cell covered, none of the other side's). A side with no placement
+    /// gains nothing.
+    pub fn best_claim_margin(&self, board: &Board, mover: Cell, piece_offsets: &[PieceOffset], depth: u8) -> i32 {
+        let other = if mover == Cell::Player1 { Cell::Player2 } else { Cell::Player1 };
+        let mut best: Option<i32> = None;
+        for (x, y) in placements_on(board, mover, piece_offsets) {
+            let claimed = (piece_offsets.len() - 1) as i32;
+            let margin = if depth == 0 {
+                claimed
+            } else {
+                let mut after = board.clone();
+                place_on(&mut after, x, y, piece_offsets, mover);
+                claimed - self.best_claim_margin(&after, other, piece_offsets, depth - 1)
+            };
+            if best.is_none_or(|top| margin > top) {
+                best = Some(margin);
+            }
+        }
+        best.unwrap_or(0)
+    }
+}
+
+/// Standard-rule (trimmed) anchors of a piece for `mover` on a board
+fn placements_on(board: &Board, mover: Cell, piece_offsets: &[PieceOffset]) -> Vec<(i32, i32)> {
+    let width = piece_offsets.iter().map(|o| o.dx() + 1).max().unwrap_or(0);
+    let height = piece_offsets.iter().map(|o| o.dy() + 1).max().unwrap_or(0);
+    if width == 0 || width > board.width() as i32 || height > board.height() as i32 {
+        return Vec::new();
+    }
+    let mut anchors = Vec::new();
+    for y in 0..=board.height() as i32 - height {
+        for x in 0..=board.width() as i32 - width {
+            let mut overlaps = 0;
+            let legal = piece_offsets.iter().all(|off| {
+                let cell = board.at((x + off.dx()) as usize, (y + off.dy()) as usize);
+                if cell == mover {
+                    overlaps += 1;
+                }
+                cell == Cell::Empty || (cell == mover && overlaps == 1)
+            });
+            if legal && overlaps == 1 {
+                anchors.push((x, y));
+            }
+        }
+    }
+    anchors
+}
+
+/// Give every cell under a (trimmed) placement to `owner`
+fn place_on(board: &mut Board, x: i32, y: i32, piece_offsets: &[PieceOffset], owner: Cell) {
+    for off in piece_offsets {
+        let (bx, by) = (x + off.dx(), y + off.dy());
+        if bx >= 0 && by >= 0 && (bx as usize) < board.width() && (by as usize) < board.height() {
+            board.put(bx as usize, by as usize, owner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_state(row: &str) -> (GameState, Vec<PieceOffset>) {
+        let frame = format!("$$$ exec p1 : [bot]\nAnfield {} 1:\n    0\n000 {}\nPiece 2 1:\n##\n", row.len(), row);
+        let mut state = GameState::new();
+        let (offsets, _, _) = state.parse_frame(&frame).unwrap();
+        (state, offsets)
+    }
+
+    #[test]
+    fn claim_margin_nets_out_the_replies() {
+        let (state, offsets) = row_state("@....$");
+        assert_eq!(state.best_claim_margin(&state.board, Cell::Player1, &offsets, 0), 1);
+        // Each side claims one cell per turn on this row
+        assert_eq!(state.best_claim_margin(&state.board, Cell::Player1, &offsets, 1), 0);
+        assert_eq!(state.best_claim_margin(&state.board, Cell::Player1, &offsets, 2), 1);
+    }
+
+    #[test]
+    fn lookahead_charges_the_best_reply() {
+        let (state, offsets) = row_state("@....$");
+        assert_eq!(state.lookahead_score(0, 0, &offsets, 1000, 0), 1000);
+        assert_eq!(state.lookahead_score(0, 0, &offsets, 1000, 1), 1000 - LOOKAHEAD_CELL_VALUE);
+        // Without a reply left for them nothing is charged
+        let (state, offsets) = row_state("@..@$$");
+        assert_eq!(state.lookahead_score(0, 0, &offsets, 1000, 1), 1000);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::types::{Cell, PieceOffset};
+use super::game_state::GameState;
+use super::board::Grid;
+
+/// Consecutive point-symmetric boards before we treat the opponent as a mirrorer
+const MIRROR_DETECT_TURNS: usize = 3;
+
+impl GameState {
+    /// Average position of the opponent's cells, if they have any
+    pub fn opponent_centroid(&self) -> Option<(f32, f32)> {
+        self.centroid_of(self.opponent_cell())
+    }
+
+    /// Average position of our cells, if we have any
+    pub fn my_centroid(&self) -> Option<(f32, f32)> {
+        self.centroid_of(self.my_cell())
+    }
+
+    fn centroid_of(&self, owner: Cell) -> Option<(f32, f32)> {
+        let (mut sum_x, mut sum_y, mut count) = (0usize, 0usize, 0usize);
+        for (x, y, cell) in self.board.cells() {
+            if cell == owner {
+                sum_x += x;
+                sum_y += y;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return None;
+        }
+        Some((sum_x as f32 / count as f32, sum_y as f32 / count as f32))
+    }
+
+    /// Reflect a point through the board center (tit-for-tat target)
+    pub fn mirror_target(&self, last_opponent_centroid: (f32, f32)) -> (f32, f32) {
+        let (cx, cy) = last_opponent_centroid;
+        (
+            (self.board_width as f32 - 1.0) - cx,
+            (self.board_height as f32 - 1.0) - cy,
+        )
+    }
+
+    /// Manhattan distance from a placement's claimed cells to the mirrored opponent centroid
+    pub fn mirror_distance(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> Option<f32> {
+        let centroid = self.last_opponent_centroid.or_else(|| self.opponent_centroid())?;
+        if piece_offsets.is_empty() {
+            return None;
+        }
+        let (tx, ty) = self.mirror_target(centroid);
+        let n = piece_offsets.len() as f32;
+        let px = piece_offsets.iter().map(|o| (x + o.dx()) as f32).sum::<f32>() / n;
+        let py = piece_offsets.iter().map(|o| (y + o.dy()) as f32).sum::<f32>() / n;
+        Some((px - tx).abs() + (py - ty).abs())
+    }
+
+    /// Whether every cell of ours reflects through the board center onto one of
+    /// theirs and vice versa (what a mirroring opponent keeps the board at)
+    pub fn is_point_mirrored(&self) -> bool {
+        let (my, op) = (self.my_cell(), self.opponent_cell());
+        let (w, h) = (self.board_width, self.board_height);
+        let mut any = false;
+        for (x, y, cell) in self.board.cells() {
+            let reflected = self.board.at(w - 1 - x, h - 1 - y);
+            let expected = if cell == my { op } else if cell == op { my } else { Cell::Empty };
+            if reflected != expected {
+                return false;
+            }
+            any |= cell == my;
+        }
+        any
+    }
+
+    /// Update the mirroring streak from the latest board (called once per board read)
+    pub fn record_mirroring(&mut self) {
+        self.mirror_streak = if self.is_point_mirrored() { self.mirror_streak + 1 } else { 0 };
+    }
+
+    /// Whether the opponent has mirrored us for the last few boards
+    pub fn opponent_is_mirroring(&self) -> bool {
+        self.mirror_streak >= MIRROR_DETECT_TURNS
+    }
+
+    /// Whether `anti_mirror` scoring is in effect this turn
+    pub fn anti_mirror_active(&self) -> bool {
+        self.anti_mirror && self.opponent_is_mirroring()
+    }
+
+    /// Manhattan distance of a cell from the board center, in half-cells
+    ///
+    /// 0 only for the exact center cell of an odd-sized board; on even sides
+    /// the central cells are a half-cell off.
+    pub fn doubled_center_distance(&self, x: i32, y: i32) -> i32 {
+        (2 * x - (self.board_width as i32 - 1)).abs() + (2 * y - (self.board_height as i32 - 1)).abs()
+    }
+
+    /// Whether a cell is (one of) the board's central cells, which a mirrorer
+    /// can only answer on the cells beside them
+    pub fn is_center_cell(&self, x: i32, y: i32) -> bool {
+        let off_center = ((self.board_width + 1) % 2 + (self.board_height + 1) % 2) as i32;
+        self.doubled_center_distance(x, y) <= off_center
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MIRROR_DETECT_TURNS;
+    use crate::game::testing::frame;
+
+    #[test]
+    fn mirror_bias_pulls_toward_the_reflected_opponent() {
+        let mut rows = vec!["...........".to_string(); 11];
+        rows[1] = "........$$.".to_string();
+        rows[5] = "....@@.....".to_string();
+        let (mut state, offsets, tx, ty) = frame(&rows, &["##", "#."]);
+        assert_eq!(state.mirror_target(state.opponent_centroid().unwrap()), (1.5, 9.0));
+
+        let plain = state.best_move(&offsets, tx, ty).unwrap();
+        state.mirror_weight = 200;
+        let mirrored = state.best_move(&offsets, tx, ty).unwrap();
+        let distance = |(x, y): (i32, i32)| state.mirror_distance(x + tx, y + ty, &offsets).unwrap();
+        assert!(distance(mirrored) < distance(plain), "{:?} vs {:?}", mirrored, plain);
+        assert!(mirrored.0 < 5 && mirrored.1 >= 4, "{:?}", mirrored);
+    }
+
+    #[test]
+    fn anti_mirror_takes_the_center_once_mirroring_is_detected() {
+        let rows = [".........", ".........", ".........", ".........", "...@.$...", ".........", ".........", ".........", "........."];
+        let (mut state, offsets, tx, ty) = frame(&rows, &["##"]);
+        assert!(state.is_point_mirrored());
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 4)));
+
+        // Turned on, but nothing happens until the mirroring has lasted a few boards
+        state.anti_mirror = true;
+        for _ in 1..MIRROR_DETECT_TURNS {
+            state.record_mirroring();
+        }
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 4)));
+        state.record_mirroring();
+        assert!(state.anti_mirror_active());
+        // Onto the center cell (4, 4), which they cannot answer
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((3, 4)));
+        assert!(state.is_center_cell(4, 4));
+
+        // One asymmetric board resets the streak
+        state.set_cell(0, 0, crate::types::Cell::Player1);
+        state.record_mirroring();
+        assert!(!state.anti_mirror_active());
+    }
+}
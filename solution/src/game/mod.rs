@@ -1,10 +1,36 @@
 // Game module - Core game logic and state management
 
 pub mod game_state;
+pub mod board;
 pub mod move_validation;
 pub mod scoring;
 pub mod strategy;
 pub mod move_execution;
+pub mod frontier;
+pub mod turn;
+pub mod small_board;
+pub mod reachability;
+pub mod compactness;
+pub mod config;
+pub mod persistence;
+pub mod mirror;
+pub mod packed_board;
+pub mod render;
+pub mod opening;
+pub mod tempo;
+pub mod board_diff;
+pub mod threat;
+pub mod repl;
+pub mod voronoi;
+pub mod orientation;
+pub mod lookahead;
+#[cfg(test)]
+mod testing;
 
 // Re-export the main GameState for easy access
 pub use game_state::GameState;
+pub use config::ScoringConfig;
+pub use scoring::TurnContext;
+pub use packed_board::PackedBoard;
+pub use board::{Board, Grid};
+pub use repl::run_repl;
@@ -1,41 +1,912 @@
-use crate::types::PieceOffset;
-use super::game_state::GameState;
+use crate::types::{MoveAnalysis, PieceOffset, ScoredMove, Strategy};
+use super::config::ScoringConfig;
+use super::scoring::TurnContext;
+use super::game_state::{to_board_index, GameState};
+use super::board::{Board, Grid};
+use super::move_validation::{piece_filled_count, piece_orientations};
+use std::cmp::Ordering;
 use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How many of the best moves softmax selection samples from
+const SOFTMAX_TOP_K: usize = 5;
+
+#[cfg(test)]
+thread_local! {
+    /// Whether `consider_move` may skip the lookahead of a beaten candidate
+    static EARLY_EXIT: std::cell::Cell<bool> = const { std::cell::Cell::new(true) };
+}
 
 impl GameState {
-    pub fn make_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) {
-        let distance_map = self.calculate_distance_map();
-    
+    /// Choose the best placement and return it as engine (untrimmed) coordinates
+    pub fn best_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        self.choose_move(piece_offsets, trim_off_x, trim_off_y)
+            .map(|(x, y, _)| (x, y))
+    }
+
+    /// Same as `best_move`, plus the chosen move's score
+    ///
+    /// A score under `losing_score_floor` (see `is_losing_score`) means even our
+    /// best option is poor, so a wrapper may switch to a desperation strategy.
+    pub fn best_move_with_eval(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32, i32)> {
+        let (x, y, score) = self.choose_move(piece_offsets, trim_off_x, trim_off_y)?;
+        // A forced move skips scoring, so it is only scored when its value is asked for
+        let score = score.unwrap_or_else(|| self.score_move(x + trim_off_x, y + trim_off_y, &self.turn_context(piece_offsets)));
+        Some((x, y, score))
+    }
+
+    /// Engine coordinates of the best placement, and its score unless it was forced
+    fn choose_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32, Option<i32>)> {
+        #[cfg(debug_assertions)]
+        if self.log_rejections {
+            eprintln!("Rejected candidates: {:?}", self.rejection_histogram(piece_offsets, trim_off_x, trim_off_y));
+        }
+
+        if let Some((x, y)) = self.forced_move(piece_offsets, trim_off_x, trim_off_y) {
+            return Some((x, y, None));
+        }
+        let result = self.search_best_move(piece_offsets, trim_off_x, trim_off_y);
+
+        #[cfg(debug_assertions)]
+        if let Some((_, _, score)) = result {
+            if self.is_losing_score(score) {
+                eprintln!("Best move scores {} (below floor {}): likely losing", score, self.losing_score_floor);
+            }
+        }
+
+        result.map(|(x, y, score)| (x, y, Some(score)))
+    }
+
+    /// The only legal placement as engine coordinates, if there is exactly one
+    ///
+    /// Found without a distance map or any scoring: there is nothing to choose.
+    fn forced_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        let mut legal = self.legal_moves_iter(piece_offsets, trim_off_x, trim_off_y);
+        if let (Some((x, y)), None) = (legal.next(), legal.next()) {
+            #[cfg(debug_assertions)]
+            eprintln!("Forced move: {} {} is the only legal placement", x - trim_off_x, y - trim_off_y);
+            return Some((x - trim_off_x, y - trim_off_y));
+        }
+        None
+    }
+
+    /// `best_move` within a time budget, degrading to `fast_greedy_move`
+    ///
+    /// Times the scoring of one candidate and projects it over every legal
+    /// placement; if the full search would not fit in what is left of the
+    /// budget, the greedy scan is played instead.
+    pub fn best_move_timed(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32, budget: Duration) -> Option<(i32, i32)> {
+        let start = Instant::now();
+        let legal = self.cap_candidates(self.find_legal_moves(piece_offsets, trim_off_x, trim_off_y), piece_offsets);
+        let (x0, y0) = match legal.first() {
+            Some(&mv) => mv,
+            None => return self.fast_greedy_move(piece_offsets, trim_off_x, trim_off_y),
+        };
+        if legal.len() == 1 {
+            // Nothing to time: a forced move is found without scoring
+            return self.best_move(piece_offsets, trim_off_x, trim_off_y);
+        }
+
+        let probe = Instant::now();
+        self.score_move(x0, y0, &self.turn_context(piece_offsets));
+        let projected = probe.elapsed() * legal.len() as u32;
+
+        if start.elapsed() + projected > budget {
+            #[cfg(debug_assertions)]
+            eprintln!("Projected search time {:?} exceeds budget {:?}: playing greedy", projected, budget);
+            return self.fast_greedy_move(piece_offsets, trim_off_x, trim_off_y);
+        }
+        self.best_move(piece_offsets, trim_off_x, trim_off_y)
+    }
+
+    /// Placement claiming the most empty cells, found in one cheap pass
+    ///
+    /// Ignores every positional heuristic; ties go to the top-left anchor and
+    /// the scan stops early at a placement no other can beat. Returns engine
+    /// (untrimmed) coordinates.
+    pub fn fast_greedy_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        if self.piece_width > self.board_width || self.piece_height > self.board_height {
+            return None;
+        }
+        let max_claim = self.max_claim(piece_offsets);
+        let end_x = self.board_width as i32 - self.piece_width as i32 + trim_off_x;
+        let end_y = self.board_height as i32 - self.piece_height as i32 + trim_off_y;
+
+        let mut best: Option<(i32, i32, usize)> = None;
+        'scan: for y in trim_off_y..=end_y {
+            for x in trim_off_x..=end_x {
+                if !self.is_legal_move(x, y, piece_offsets) {
+                    continue;
+                }
+                let claimed = self.count_claimed_cells(x, y, piece_offsets);
+                if best.is_none_or(|(_, _, most)| claimed > most) {
+                    best = Some((x, y, claimed));
+                    if claimed == max_claim {
+                        break 'scan;
+                    }
+                }
+            }
+        }
+
+        // Convert TRIMMED anchor → ORIGINAL top-left for the engine
+        best.map(|(x, y, _)| ((x - trim_off_x).max(0), (y - trim_off_y).max(0)))
+    }
+
+    /// Move of a single-feature baseline strategy (trimmed anchor)
+    ///
+    /// `MaxArea` claims the most empty cells, `MaxBlock` makes the most
+    /// opponent contacts; ties go to the top-left anchor.
+    pub fn baseline_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        let mut best: Option<((i32, i32), usize)> = None;
+        for (x, y) in self.find_legal_moves(piece_offsets, trim_off_x, trim_off_y) {
+            let key = match self.strategy {
+                Strategy::MaxBlock => self.count_opponent_contacts(x, y, piece_offsets),
+                Strategy::MaxArea | Strategy::Heuristic => self.count_claimed_cells(x, y, piece_offsets),
+            };
+            if best.is_none_or(|(_, top)| key > top) {
+                best = Some(((x, y), key));
+            }
+        }
+        best.map(|(mv, _)| mv)
+    }
+
+    /// Check whether a best-move score signals a likely-losing position
+    pub fn is_losing_score(&self, score: i32) -> bool {
+        score < self.losing_score_floor
+    }
+
+    /// Best placement as engine coordinates plus its score
+    ///
+    /// Callers check for a forced move (see `forced_move`) first.
+    fn search_best_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32, i32)> {
+        if self.strategy != Strategy::Heuristic {
+            let (x, y) = self.baseline_move(piece_offsets, trim_off_x, trim_off_y)?;
+            let score = self.score_move(x, y, &self.turn_context(piece_offsets));
+            return Some(((x - trim_off_x).max(0), (y - trim_off_y).max(0), score));
+        }
+
+        // A single cell can only land on one of ours: no need to scan the whole board
+        if piece_filled_count(piece_offsets) == 1 && self.required_overlaps() == 1 {
+            if let Some((x, y)) = self.single_cell_move(piece_offsets, trim_off_x, trim_off_y) {
+                let score = self.score_move(x, y, &self.turn_context(piece_offsets));
+                return Some((x - trim_off_x, y - trim_off_y, score));
+            }
+        }
+
+        // The phase shortcuts below ignore scoring, so key squares and the
+        // anti-mirror center pull would never count
+        let scripted = !self.key_squares.is_empty() || self.anti_mirror_active();
+
+        // Won: nothing left to fight for, so just fill without leaving holes
+        if !scripted && self.count_opponent_territory() > 0 && self.is_won() {
+            if let Some((x, y)) = self.safe_fill_move(piece_offsets, trim_off_x, trim_off_y) {
+                let score = self.score_move(x, y, &self.turn_context(piece_offsets));
+                return Some(((x - trim_off_x).max(0), (y - trim_off_y).max(0), score));
+            }
+        }
+
+        // Boxed in: stretch out the turns we can still play rather than grab cells
+        if !scripted && self.in_survival_mode() {
+            if let Some((x, y)) = self.survival_move(piece_offsets, trim_off_x, trim_off_y) {
+                let score = self.score_move(x, y, &self.turn_context(piece_offsets));
+                return Some(((x - trim_off_x).max(0), (y - trim_off_y).max(0), score));
+            }
+        }
+
+        // Comfortably ahead with plenty left: seal the opponent in rather than race for cells
+        if !scripted && self.protecting_lead() {
+            if let Some((x, y)) = self.wall_move(piece_offsets, trim_off_x, trim_off_y) {
+                let score = self.score_move(x, y, &self.turn_context(piece_offsets));
+                return Some(((x - trim_off_x).max(0), (y - trim_off_y).max(0), score));
+            }
+        }
+
+        // Opening: nothing to connect to yet, so position the blob first
+        if !scripted && self.is_opening() && self.count_opponent_territory() > 0 {
+            if let Some((x, y)) = self.opening_move(piece_offsets, trim_off_x, trim_off_y) {
+                let score = self.score_move(x + trim_off_x, y + trim_off_y, &self.turn_context(piece_offsets));
+                return Some((x, y, score));
+            }
+        }
+
+        // Tiny boards take the allocation-light path
+        if self.is_small_board() {
+            return self.best_move_small(piece_offsets, trim_off_x, trim_off_y);
+        }
+        self.best_move_general(piece_offsets, trim_off_x, trim_off_y)
+    }
+
+    /// Score every (capped) legal move and keep the best, on a board of any size
+    ///
+    /// Returns engine coordinates and the score; `best_move_small` must agree
+    /// with this on every board it handles.
+    pub(crate) fn best_move_general(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32, i32)> {
+        let ctx = self.turn_context(piece_offsets);
+
         // Find legal moves with offset-aware scan
         let mut legal_moves = self.find_legal_moves(piece_offsets, trim_off_x, trim_off_y);
-    
+
         if legal_moves.is_empty() {
             legal_moves = self.emergency_move_search(piece_offsets, trim_off_x, trim_off_y);
         }
-    
+
         if legal_moves.is_empty() {
-            println!("0 0");
-        } else {
-            let mut best = legal_moves[0];
-            let mut best_score = self.score_move(best.0, best.1, &distance_map, piece_offsets);
-    
-            for &(x, y) in &legal_moves {
-                let s = self.score_move(x, y, &distance_map, piece_offsets);
-                if s > best_score {
-                    best_score = s;
-                    best = (x, y);
+            return None;
+        }
+        let mut legal_moves = self.cap_candidates(legal_moves, piece_offsets);
+
+        // Endgame: maximal claims tend to score best, so score them first and
+        // let the rest skip their lookahead once they fall behind
+        if self.game_progress_permille() >= self.late_phase_start_permille() {
+            let max_claim = self.max_claim(piece_offsets);
+            legal_moves.sort_by_key(|&(x, y)| self.count_claimed_cells(x, y, piece_offsets) != max_claim);
+        }
+
+        let depth = self.adaptive_depth(legal_moves.len());
+
+        // Minimal logging
+        #[cfg(debug_assertions)]
+        eprintln!("Legal moves: {}, lookahead depth: {}", legal_moves.len(), depth);
+
+        let mut best = None;
+        for &(x, y) in &legal_moves {
+            self.consider_move(&mut best, x, y, piece_offsets, &ctx, depth);
+        }
+        let best = best?;
+
+        // Convert TRIMMED anchor → ORIGINAL top-left for the engine
+        let out_x = best.x - trim_off_x;
+        let out_y = best.y - trim_off_y;
+        // Safety (should already be ≥0 and within board)
+        Some((out_x.max(0), out_y.max(0), best.score))
+    }
+
+    /// Score a candidate and keep it in `best` if `compare_moves` ranks it higher
+    ///
+    /// `compare_moves` orders every pair of distinct anchors, so the winner does
+    /// not depend on the order candidates are offered in. The lookahead only
+    /// ever lowers a score, so a candidate whose static score is already below
+    /// the best exits before it.
+    pub(crate) fn consider_move(&self, best: &mut Option<ScoredMove>, x: i32, y: i32, piece_offsets: &[PieceOffset], ctx: &TurnContext, depth: u8) {
+        let score = self.score_move(x, y, ctx);
+        let beaten = best.as_ref().is_some_and(|current| score < current.score);
+        #[cfg(test)]
+        let beaten = beaten && EARLY_EXIT.with(|early_exit| early_exit.get());
+        if beaten {
+            return;
+        }
+        let score = self.lookahead_score(x, y, piece_offsets, score, depth);
+        let candidate = ScoredMove::new(x, y, score);
+        if best.as_ref().is_none_or(|current| self.compare_moves(&candidate, current, piece_offsets) == Ordering::Greater) {
+            *best = Some(candidate);
+        }
+    }
+
+    /// Placement of a one-cell piece: our cell with the most empty neighbors
+    ///
+    /// Such a piece covers only the own cell it must overlap, so its legal
+    /// placements are exactly our cells and none claims anything; a frontier
+    /// cell is the natural pick. Ties go to the top-left cell. Only anchors
+    /// `find_legal_moves` would scan are considered, so the printed move never
+    /// leaves the board. Returns a trimmed anchor.
+    pub fn single_cell_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        let end_x = self.board_width as i32 - self.piece_width as i32 + trim_off_x;
+        let end_y = self.board_height as i32 - self.piece_height as i32 + trim_off_y;
+        let mut best: Option<((i32, i32), i32)> = None;
+        for (x, y) in self.get_my_territory_positions() {
+            let (ax, ay) = (x as i32, y as i32);
+            if ax < trim_off_x || ax > end_x || ay < trim_off_y || ay > end_y || !self.is_legal_move(ax, ay, piece_offsets) {
+                continue;
+            }
+            let open = self.count_empty_neighbors(x, y);
+            if best.is_none_or(|(_, top)| open > top) {
+                best = Some(((ax, ay), open));
+            }
+        }
+        best.map(|(mv, _)| mv)
+    }
+
+    /// Conservative fill for a won game: fewest new holes, then most cells claimed
+    ///
+    /// Ties go to the top-left anchor. Returns a trimmed anchor.
+    pub fn safe_fill_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        let mut best: Option<((i32, i32), (i32, usize))> = None;
+        for (x, y) in self.find_legal_moves(piece_offsets, trim_off_x, trim_off_y) {
+            let key = (-self.enclosed_holes_created(x, y, piece_offsets), self.count_claimed_cells(x, y, piece_offsets));
+            if best.is_none_or(|(_, top)| key > top) {
+                best = Some(((x, y), key));
+            }
+        }
+        best.map(|(mv, _)| mv)
+    }
+
+    /// Whether the space left to us has shrunk below `survival_threshold`
+    pub fn in_survival_mode(&self) -> bool {
+        self.survival_threshold > 0 && self.reachable_empty_from_my_territory() < self.survival_threshold
+    }
+
+    /// Placement keeping us playable longest: most open cells bordering us
+    /// afterwards, then the most space left to grow into (fewest claimed)
+    ///
+    /// Ties go to the top-left anchor. Returns a trimmed anchor.
+    pub fn survival_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        let mut best: Option<((i32, i32), (usize, usize))> = None;
+        for (x, y) in self.legal_moves_iter(piece_offsets, trim_off_x, trim_off_y) {
+            let after = self.simulate_move(x, y, piece_offsets);
+            let key = (self.next_turn_mobility_estimate(&after), self.reachable_empty_from_my_territory_on(&after));
+            if best.is_none_or(|(_, top)| key > top) {
+                best = Some(((x, y), key));
+            }
+        }
+        best.map(|(mv, _)| mv)
+    }
+
+    /// Whether we lead by `wall_lead_percent` with the endgame still ahead
+    pub fn protecting_lead(&self) -> bool {
+        let (my_t, op_t) = (self.count_my_territory() as i64, self.count_opponent_territory() as i64);
+        self.wall_lead_percent > 0
+            && op_t > 0
+            && my_t * 100 >= op_t * (100 + self.wall_lead_percent as i64)
+            && self.game_progress_permille() < self.late_phase_start_permille()
+    }
+
+    /// Wall-building placement: most opponent space sealed off, then most contact
+    /// with their border, then most cells claimed
+    ///
+    /// `None` when no placement seals or touches anything, leaving the choice to
+    /// the heuristic. Ties go to the top-left anchor. Returns a trimmed anchor.
+    pub fn wall_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        // (sealed, contacts, claimed); a move must beat all zeros to count as a wall
+        let mut best_key = (0, 0, 0);
+        let mut best = None;
+        let reached = self.opponent_reachability();
+        for (x, y) in self.find_legal_moves(piece_offsets, trim_off_x, trim_off_y) {
+            let key = (
+                self.opponent_region_captured_from(&reached, x, y, piece_offsets),
+                self.count_opponent_contacts(x, y, piece_offsets),
+                self.count_claimed_cells(x, y, piece_offsets),
+            );
+            if key > best_key && (key.0 > 0 || key.1 > 0) {
+                best_key = key;
+                best = Some((x, y));
+            }
+        }
+        best
+    }
+
+    /// Every legal placement with its score, best first (trimmed anchors)
+    pub fn scored_moves(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Vec<ScoredMove> {
+        let ctx = self.turn_context(piece_offsets);
+        let mut moves: Vec<ScoredMove> = self
+            .find_legal_moves(piece_offsets, trim_off_x, trim_off_y)
+            .into_iter()
+            .map(|(x, y)| ScoredMove::new(x, y, self.score_move(x, y, &ctx)))
+            .collect();
+        moves.sort_by(|a, b| self.compare_moves(b, a, piece_offsets));
+        moves
+    }
+
+    /// Every legal placement with its score broken down by term, best first (trimmed anchors)
+    pub fn move_analysis(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Vec<MoveAnalysis> {
+        let ctx = self.turn_context(piece_offsets);
+        let mut analyses: Vec<MoveAnalysis> = self
+            .find_legal_moves(piece_offsets, trim_off_x, trim_off_y)
+            .into_iter()
+            .filter_map(|(x, y)| self.analyze_move(x, y, &ctx))
+            .collect();
+        analyses.sort_by(|a, b| {
+            self.compare_moves(&ScoredMove::new(b.x, b.y, b.score), &ScoredMove::new(a.x, a.y, a.score), piece_offsets)
+        });
+        analyses
+    }
+
+    /// Sample among the top-scoring moves instead of always taking the best
+    ///
+    /// Each of the best few moves is picked with probability proportional to
+    /// `exp(score / temperature)`, driven by a seeded generator so a given seed
+    /// always picks the same move. A temperature of 0 is plain `best_move`.
+    pub fn best_move_softmax(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32, temperature: f32, seed: u64) -> Option<(i32, i32)> {
+        if temperature <= 0.0 {
+            return self.best_move(piece_offsets, trim_off_x, trim_off_y);
+        }
+        let mut moves = self.scored_moves(piece_offsets, trim_off_x, trim_off_y);
+        if moves.is_empty() {
+            return self.best_move(piece_offsets, trim_off_x, trim_off_y);
+        }
+        moves.truncate(SOFTMAX_TOP_K);
+
+        // Shift by the best score so the largest weight is exactly 1
+        let top = moves[0].score as f64;
+        let weights: Vec<f64> = moves
+            .iter()
+            .map(|m| ((m.score as f64 - top) / temperature as f64).exp())
+            .collect();
+        let mut pick = unit_interval(splitmix64(seed)) * weights.iter().sum::<f64>();
+        let mut chosen = &moves[moves.len() - 1];
+        for (m, w) in moves.iter().zip(&weights) {
+            if pick < *w {
+                chosen = m;
+                break;
+            }
+            pick -= w;
+        }
+
+        // Convert TRIMMED anchor → ORIGINAL top-left for the engine
+        Some(((chosen.x - trim_off_x).max(0), (chosen.y - trim_off_y).max(0)))
+    }
+
+    /// Which move each scoring profile would choose on the current board
+    ///
+    /// Diagnostic only: profiles are evaluated in parallel on copies of this
+    /// state. Profiles with no legal move are left out.
+    pub fn evaluate_profiles(&self, profiles: &[ScoringConfig], piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Vec<(usize, (i32, i32))> {
+        thread::scope(|scope| {
+            let handles: Vec<_> = profiles
+                .iter()
+                .enumerate()
+                .map(|(idx, profile)| {
+                    scope.spawn(move || {
+                        let mut state = self.clone();
+                        state.apply_scoring_config(profile);
+                        state.best_move(piece_offsets, trim_off_x, trim_off_y).map(|mv| (idx, mv))
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().expect("profile evaluation panicked"))
+                .collect()
+        })
+    }
+
+    /// Board as it would look after placing the piece at this (trimmed) anchor
+    ///
+    /// Every covered cell becomes ours, so a captured opponent cell changes hands.
+    pub fn simulate_move(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> Board {
+        let mut board = self.board.clone();
+        let my = self.my_cell();
+        for off in piece_offsets {
+            if let Some((bx, by)) = to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height) {
+                board.put(bx, by, my);
+            }
+        }
+        board
+    }
+
+    /// Place the piece at this (trimmed) anchor on our own board
+    ///
+    /// For self-play and analysis; like `simulate_move`, but in place, so the
+    /// packed board and `empty_cells` follow.
+    pub fn apply_move(&mut self, x: i32, y: i32, piece_offsets: &[PieceOffset]) {
+        let my = self.my_cell();
+        for off in piece_offsets {
+            if let Some((bx, by)) = to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height) {
+                self.set_cell(bx, by, my);
+            }
+        }
+    }
+
+    pub fn make_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        self.make_move_to(&mut out, piece_offsets, trim_off_x, trim_off_y).unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Write our move to `out` without flushing
+    ///
+    /// The engine needs a flush every turn (`make_move` does that); simulations
+    /// can pass a buffered writer and flush when they like.
+    pub fn make_move_to<W: Write>(&self, out: &mut W, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> io::Result<()> {
+        let (_, ox, oy) = self.make_move_multi(&[(piece_offsets.to_vec(), trim_off_x, trim_off_y)]);
+        writeln!(out, "{}", self.format_move(ox, oy))
+    }
+
+    /// Pick which of several offered pieces to place, and where (variant rule)
+    ///
+    /// Each entry is a piece's trimmed offsets and trim offset, as returned by
+    /// `trim_piece`. The best placement of every piece is found and the highest
+    /// score wins, earlier pieces on ties. Returns the piece index and engine
+    /// coordinates, or `(0, 0, 0)` (the usual `0 0` forfeit) if nothing fits.
+    pub fn make_move_multi(&self, pieces: &[(Vec<PieceOffset>, i32, i32)]) -> (usize, i32, i32) {
+        self.best_piece_placement(pieces).map_or((0, 0, 0), |(idx, x, y)| (idx, x, y))
+    }
+
+    /// `best_move` over every orientation of the piece (variant rule)
+    ///
+    /// Without `rotations_allowed` only the given orientation is tried. Returns
+    /// the engine coordinates and the index into `piece_orientations`; rotated
+    /// pieces are placed by their trimmed bounding box, so only orientation 0
+    /// applies the trim offset.
+    pub fn best_move_oriented(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32, usize)> {
+        if !self.rotations_allowed {
+            return self.best_move(piece_offsets, trim_off_x, trim_off_y).map(|(x, y)| (x, y, 0));
+        }
+        let pieces: Vec<(Vec<PieceOffset>, i32, i32)> = piece_orientations(piece_offsets)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, offsets)| if idx == 0 { (offsets, trim_off_x, trim_off_y) } else { (offsets, 0, 0) })
+            .collect();
+        self.best_piece_placement(&pieces).map(|(idx, x, y)| (x, y, idx))
+    }
+
+    /// Highest-scoring (piece index, engine x, engine y) over several pieces, earlier pieces on ties
+    fn best_piece_placement(&self, pieces: &[(Vec<PieceOffset>, i32, i32)]) -> Option<(usize, i32, i32)> {
+        let mut best: Option<(usize, i32, i32, i32)> = None;
+        for (idx, (piece_offsets, trim_off_x, trim_off_y)) in pieces.iter().enumerate() {
+            // The scan bounds come from the piece size, so score other pieces on a resized copy
+            let width = piece_offsets.iter().map(|o| o.dx() + 1).max().unwrap_or(0) as usize;
+            let height = piece_offsets.iter().map(|o| o.dy() + 1).max().unwrap_or(0) as usize;
+            let resized;
+            let state = if (width, height) == (self.piece_width, self.piece_height) {
+                self
+            } else {
+                let mut state = self.clone();
+                state.piece_width = width;
+                state.piece_height = height;
+                resized = state;
+                &resized
+            };
+
+            // A lone piece wins whatever it scores, so a forced move stays unscored
+            let result = if pieces.len() == 1 {
+                state.best_move(piece_offsets, *trim_off_x, *trim_off_y).map(|(x, y)| (x, y, 0))
+            } else {
+                state.best_move_with_eval(piece_offsets, *trim_off_x, *trim_off_y)
+            };
+
+            if let Some((x, y, score)) = result {
+                if best.is_none_or(|(_, _, _, top)| score > top) {
+                    best = Some((idx, x, y, score));
                 }
             }
-    
-            // Convert TRIMMED anchor → ORIGINAL top-left for the engine
-            let out_x = best.0 - trim_off_x;
-            let out_y = best.1 - trim_off_y;
-            // Safety (should already be ≥0 and within board)
-            let ox = out_x.max(0);
-            let oy = out_y.max(0);
-    
-            println!("{} {}", ox, oy);
-        }
-        io::stdout().flush().unwrap();
+        }
+        best.map(|(idx, x, y, _)| (idx, x, y))
+    }
+}
+
+/// One step of the SplitMix64 generator; good enough mixing for move sampling
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Map random bits to [0, 1)
+fn unit_interval(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_state::DISTANCE_MAPS;
+    use crate::game::testing::{frame, random_rows};
+
+    #[test]
+    fn single_cell_move_stays_within_the_scan_bounds() {
+        // Trim offset (2, 2): anchors left of / above column/row 2 would print negative
+        let frame = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 5:\n    01234\n000 $....\n001 .@...\n002 .....\n003 .....\n004 ...@@\n\
+                     Piece 3 3:\n...\n...\n..#\n";
+        let mut state = GameState::new();
+        let (offsets, tx, ty) = state.parse_frame(frame).unwrap();
+        assert_eq!((tx, ty), (2, 2));
+        // (1, 1) is the most open cell but out of range
+        assert_eq!(state.single_cell_move(&offsets, tx, ty), Some((3, 4)));
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((1, 2)));
+    }
+
+    #[test]
+    fn trimmed_anchors_map_to_outputs_on_the_board() {
+        // Trim offset (1, 1): the padded piece must still start on the board
+        let (state, offsets, tx, ty) = frame(&[".....", ".@...", ".....", "....$"], &["...", ".##", "..."]);
+        assert_eq!((tx, ty), (1, 1));
+        for (x, y) in state.find_legal_moves(&offsets, tx, ty) {
+            let (out_x, out_y) = (x - tx, y - ty);
+            assert!(out_x >= 0 && out_x + 3 <= 5 && out_y >= 0 && out_y + 3 <= 4, "({}, {})", out_x, out_y);
+        }
+        // Covering (1, 1) with the right-hand cell would print x = -1
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((0, 0)));
+
+        // Ties break the same way on anchors as on outputs
+        let best = ScoredMove::new(2, 1, 5).max(ScoredMove::new(1, 2, 5));
+        assert_eq!((best.x - tx, best.y - ty), (1, 0));
+    }
+
+    #[test]
+    fn forced_move_skips_the_search_on_every_entry_point() {
+        // `##` fits only at (0, 0): our other cell is hemmed in
+        let frame = "$$$ exec p1 : [bot]\n\
+                     Anfield 6 3:\n    012345\n000 @.....\n001 $@$...\n002 ......\n\
+                     Piece 2 1:\n##\n";
+        let mut state = GameState::new();
+        let (offsets, tx, ty) = state.parse_frame(frame).unwrap();
+        assert_eq!(state.legal_move_count(&offsets, tx, ty), 1);
+
+        let distance_maps = || DISTANCE_MAPS.with(|maps| maps.get());
+        DISTANCE_MAPS.with(|maps| maps.set(0));
+        let mut out = Vec::new();
+        state.make_move_to(&mut out, &offsets, tx, ty).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "0 0\n");
+        assert_eq!(state.best_move_oriented(&offsets, tx, ty), Some((0, 0, 0)));
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((0, 0)));
+        assert_eq!(state.best_move_timed(&offsets, tx, ty, Duration::from_millis(50)), Some((0, 0)));
+        assert_eq!(distance_maps(), 0);
+
+        // Asking for its value is what scores it
+        assert_eq!(state.best_move_with_eval(&offsets, tx, ty).map(|(x, y, _)| (x, y)), Some((0, 0)));
+        assert_eq!(distance_maps(), 1);
+
+        // With a second option the search runs
+        state.set_cell(2, 1, crate::types::Cell::Empty);
+        assert_eq!(state.legal_move_count(&offsets, tx, ty), 2);
+        state.best_move(&offsets, tx, ty);
+        assert!(distance_maps() > 1);
+    }
+
+    #[test]
+    fn eliminated_opponent_still_gets_an_expansion_move() {
+        for size in &[10usize, 24] {
+            let mut rows = vec![".".repeat(*size); *size];
+            rows[size / 2] = format!("{}@@{}", ".".repeat(size / 2 - 1), ".".repeat(size - size / 2 - 1));
+            let (state, offsets, tx, ty) = frame(&rows, &["##", "#."]);
+            assert_eq!(state.count_opponent_territory(), 0);
+
+            let (x, y) = state.best_move(&offsets, tx, ty).unwrap();
+            let (ax, ay) = (x + tx, y + ty);
+            assert!(state.is_legal_move(ax, ay, &offsets), "{}: {:?}", size, (x, y));
+            assert_eq!(state.count_claimed_cells(ax, ay, &offsets), 2);
+            assert_ne!((x, y), (0, 0));
+        }
+    }
+
+    #[test]
+    fn diverging_profiles_report_their_own_moves() {
+        let rows = [".........", ".........", "$........", ".........", "@...@....", ".........", ".........", ".........", "........."];
+        let (state, offsets, tx, ty) = frame(&rows, &["##"]);
+        let greedy = ScoringConfig::default();
+        let central = ScoringConfig { centrality_weight: 10, ..ScoringConfig::default() };
+
+        let chosen = state.evaluate_profiles(&[greedy, central], &offsets, tx, ty);
+        assert_eq!(chosen, vec![(0, (0, 4)), (1, (3, 4))]);
+    }
+
+    #[test]
+    fn cramped_board_reports_a_losing_score() {
+        // One empty cell left to us, far from the opponent
+        let rows = ["$@@@@@@@@@@@@@@@", "@@@@@@@@@@@@@@@@", "@@@@@@@@@@@@@@@@", "@@@@@@@@@@@@@@@."];
+        let (state, offsets, tx, ty) = frame(&rows, &["##"]);
+        let (x, y, score) = state.best_move_with_eval(&offsets, tx, ty).unwrap();
+        assert_eq!((x, y), (14, 3));
+        assert!(state.is_losing_score(score), "score {}", score);
+
+        let (open, offsets, tx, ty) = frame(&["@.....", "......", ".....$"], &["##"]);
+        let (_, _, score) = open.best_move_with_eval(&offsets, tx, ty).unwrap();
+        assert!(!open.is_losing_score(score), "score {}", score);
+    }
+
+    #[test]
+    fn softmax_is_greedy_at_zero_and_reproducible_per_seed() {
+        let (state, offsets, tx, ty) = frame(&["..........", "...@@.....", "...@......", "..........", ".......$.."], &["##"]);
+        assert_eq!(state.best_move_softmax(&offsets, tx, ty, 0.0, 7), state.best_move(&offsets, tx, ty));
+
+        let picks: Vec<_> = (0..20).map(|seed| state.best_move_softmax(&offsets, tx, ty, 500.0, seed)).collect();
+        let again: Vec<_> = (0..20).map(|seed| state.best_move_softmax(&offsets, tx, ty, 500.0, seed)).collect();
+        assert_eq!(picks, again);
+        let legal = state.find_legal_moves(&offsets, tx, ty);
+        assert!(picks.iter().all(|mv| legal.contains(&mv.unwrap())));
+        // A high temperature spreads the choice over several moves
+        assert!(picks.iter().any(|mv| *mv != picks[0]));
+    }
+
+    #[test]
+    fn analysis_components_add_up_to_the_score() {
+        let (mut state, offsets, tx, ty) = frame(&["...........", "..@@.......", "..@........", ".......$$..", "........$.."], &["##", ".#"]);
+        state.frontier_weight = 3;
+        state.mobility_weight = 5;
+        let analyses = state.move_analysis(&offsets, tx, ty);
+        assert!(analyses.len() > 3);
+        for a in &analyses {
+            assert_eq!(a.new_cells + a.liberties + a.adj_op + a.heat_sum + a.connectivity + a.other, a.score as i64, "{:?}", a);
+        }
+        // Best first, and the top entry is what the search plays
+        assert!(analyses.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((analyses[0].x - tx, analyses[0].y - ty)));
+    }
+
+    #[test]
+    fn greedy_move_is_legal_and_claims_the_most_cells() {
+        for seed in 0..20 {
+            let (state, offsets, tx, ty) = frame(&random_rows(seed, 14, 10, 60), &["...", ".#.", "##."]);
+            let legal = state.find_legal_moves(&offsets, tx, ty);
+            let (x, y) = state.fast_greedy_move(&offsets, tx, ty).unwrap();
+            let (ax, ay) = (x + tx, y + ty);
+            assert!(state.is_legal_move(ax, ay, &offsets), "seed {}", seed);
+
+            // Same cell count as the best the full candidate list offers, first in scan order
+            let most = legal.iter().map(|&(lx, ly)| state.count_claimed_cells(lx, ly, &offsets)).max().unwrap();
+            let first = legal.iter().find(|&&(lx, ly)| state.count_claimed_cells(lx, ly, &offsets) == most);
+            assert_eq!(state.count_claimed_cells(ax, ay, &offsets), most, "seed {}", seed);
+            assert_eq!(first, Some(&(ax, ay)), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn endgame_early_exit_keeps_the_exhaustive_choice() {
+        let mut played = 0;
+        for seed in 0..40 {
+            let (width, height) = if seed % 2 == 0 { (22, 18) } else { (12, 10) };
+            let (mut state, offsets, tx, ty) = frame(&random_rows(seed, width, height, 15), &["##", "#."]);
+            assert!(state.game_progress_permille() >= state.late_phase_start_permille());
+            state.node_budget = 2_000;
+
+            EARLY_EXIT.with(|early_exit| early_exit.set(false));
+            let exhaustive = state.best_move_with_eval(&offsets, tx, ty);
+            EARLY_EXIT.with(|early_exit| early_exit.set(true));
+            assert_eq!(state.best_move_with_eval(&offsets, tx, ty), exhaustive, "seed {}", seed);
+            assert_eq!(state.best_move(&offsets, tx, ty), exhaustive.map(|(x, y, _)| (x, y)), "seed {}", seed);
+            played += exhaustive.is_some() as usize;
+        }
+        assert!(played > 20);
+    }
+
+    #[test]
+    fn candidate_cap_plays_near_our_territory() {
+        // A blob of ours on the left, and a long arm reaching for the opponent
+        let rows: Vec<String> = (0..20)
+            .map(|y| {
+                (0..30)
+                    .map(|x| match (x, y) {
+                        (2..=8, 7..=13) | (9..=24, 10) => '@',
+                        (27..=29, 8..=12) => '$',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        let (mut state, offsets, tx, ty) = frame(&rows, &["##", "#."]);
+        let legal = state.find_legal_moves(&offsets, tx, ty);
+        assert!(legal.len() > 12);
+
+        // Scoring everything, the tip of the arm wins
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((24, 9)));
+
+        // Capped, only the moves around the blob are scored and the arm is left alone
+        state.max_candidates = 12;
+        let scored = state.cap_candidates(legal, &offsets);
+        assert_eq!(scored.len(), 12);
+        assert!(scored.iter().all(|&(x, _)| x <= 13), "{:?}", scored);
+        let (x, y) = state.best_move(&offsets, tx, ty).unwrap();
+        assert!(scored.contains(&(x + tx, y + ty)));
+        assert!(state.is_legal_move(x + tx, y + ty, &offsets));
+    }
+
+    #[test]
+    fn multi_piece_offer_picks_the_piece_that_scores_best() {
+        let (state, _, _, _) = frame(&["@.....", "......", ".....$"], &["#"]);
+        let bar = |len: i32| ((0..len).map(|dx| PieceOffset::new(dx, 0)).collect::<Vec<_>>(), 0, 0);
+
+        // Too wide for the board, then two that fit: the longer claims more
+        let (idx, x, y) = state.make_move_multi(&[bar(7), bar(2), bar(3)]);
+        assert_eq!(idx, 2);
+        assert!(state.is_legal_move(x, y, &bar(3).0));
+
+        assert_eq!(state.make_move_multi(&[bar(7)]), (0, 0, 0));
+        assert_eq!(state.make_move_multi(&[]), (0, 0, 0));
+    }
+
+    #[test]
+    fn benchmark_position_picks_the_same_move_with_either_offset_type() {
+        // The `offsets` bench fixture; the gates run this with and without `compact-offsets`
+        let rows: Vec<String> = (0..60)
+            .map(|y| (0..60).map(|x| match (x, y) {
+                _ if (10..25).contains(&x) && (10..20).contains(&y) => '@',
+                _ if (35..50).contains(&x) && (38..50).contains(&y) => '$',
+                _ => '.',
+            }).collect())
+            .collect();
+        let (state, offsets, tx, ty) = frame(&rows, &[".##.", "###.", "..##"]);
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((24, 18)));
+    }
+
+    #[test]
+    fn each_strategy_plays_its_own_move() {
+        let rows = ["..........", "..........", "....@@....", "...$@.....", "...$......", "........$."];
+        let (mut state, offsets, tx, ty) = frame(&rows, &["##", "#."]);
+        let mut play = |strategy| {
+            state.strategy = strategy;
+            state.best_move(&offsets, tx, ty)
+        };
+        assert_eq!(play(Strategy::Heuristic), Some((5, 1)));
+        // First placement claiming all three cells
+        assert_eq!(play(Strategy::MaxArea), Some((4, 1)));
+        // The only placement touching the opponent
+        assert_eq!(play(Strategy::MaxBlock), Some((4, 3)));
+        let touching: Vec<_> = state.find_legal_moves(&offsets, tx, ty)
+            .into_iter()
+            .filter(|&(x, y)| state.count_opponent_contacts(x, y, &offsets) > 0)
+            .collect();
+        assert_eq!(touching, vec![(4, 3)]);
+    }
+
+    #[test]
+    fn buffered_writer_collects_several_moves_before_a_flush() {
+        let (state, offsets, tx, ty) = frame(&[".......", ".@.....", ".......", "......$"], &["##"]);
+        let (_, no_fit, nx, ny) = frame(&[".......", ".@.....", ".......", "......$"], &["########"]);
+        let mut out = std::io::BufWriter::new(Vec::new());
+        state.make_move_to(&mut out, &offsets, tx, ty).unwrap();
+        state.make_move_to(&mut out, &no_fit, nx, ny).unwrap();
+        state.make_move_to(&mut out, &offsets, tx, ty).unwrap();
+        // Nothing reaches the buffer until the caller flushes
+        assert!(out.get_ref().is_empty());
+        let written = out.into_inner().unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), "0 1\n0 0\n0 1\n");
+    }
+
+    #[test]
+    fn output_format_transforms_the_same_move() {
+        let (mut state, offsets, tx, ty) = frame(&[".......", ".@.....", ".......", "......$"], &["##"]);
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((0, 1)));
+        let mut written = |swap_xy, flip_y| {
+            state.output_format = crate::types::OutputFormat { swap_xy, flip_y };
+            let mut out = Vec::new();
+            state.make_move_to(&mut out, &offsets, tx, ty).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(written(false, false), "0 1\n");
+        assert_eq!(written(true, false), "1 0\n");
+        // Four rows: row 1 from the top is row 2 from the bottom
+        assert_eq!(written(false, true), "0 2\n");
+        assert_eq!(written(true, true), "2 0\n");
+    }
+
+    #[test]
+    fn leading_side_walls_the_opponent_out_of_the_pocket() {
+        // Ahead 16 cells to 1 with most of the board still open
+        let rows = ["$......", ".......", "......@", "@@@.@@@", "@.....@", "@@@@@@@"];
+        let (mut state, offsets, tx, ty) = frame(&rows, &["#", "#"]);
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((1, 2)));
+
+        // Dropping into the gap's foot cuts both halves of the pocket off from them
+        state.wall_lead_percent = 50;
+        assert!(state.protecting_lead() && !state.is_won());
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((3, 4)));
+        assert_eq!(state.opponent_region_captured_by(3, 4, &offsets), 4);
+    }
+
+    #[test]
+    fn rotation_opens_a_placement_the_given_orientation_lacks() {
+        // The L's long side would run into their cell under ours
+        let (mut state, offsets, tx, ty) = frame(&["@.$...", "$.....", "......", ".....$"], &["#.", "##"]);
+        assert_eq!(state.best_move_oriented(&offsets, tx, ty), None);
+
+        state.rotations_allowed = true;
+        // Turned twice it hangs to the right of our cell instead
+        assert_eq!(state.best_move_oriented(&offsets, tx, ty), Some((0, 0, 2)));
+        let rotated = &piece_orientations(&offsets)[2];
+        assert_eq!(rotated, &vec![PieceOffset::new(0, 0), PieceOffset::new(1, 0), PieceOffset::new(1, 1)]);
+        assert!(state.is_legal_move(0, 0, rotated));
+    }
+
+    #[test]
+    fn survival_mode_keeps_the_longer_way_out_open() {
+        // Walled in by them: a one-cell dead end to our left, a winding corridor to the right
+        let rows = ["$$$$$$$", "$$.@..$", "$$$$$.$", "$.....$", "$$$$$$$"];
+        let (mut state, offsets, tx, ty) = frame(&rows, &["##"]);
+        assert_eq!(state.reachable_empty_from_my_territory(), 9);
+        let mobility = |x| state.next_turn_mobility_estimate(&state.simulate_move(x, 1, &offsets));
+        assert_eq!((mobility(2), mobility(3)), (1, 2));
+
+        // Normal play fills the dead end
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 1)));
+        state.survival_threshold = 10;
+        assert!(state.in_survival_mode());
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((3, 1)));
+        // The threshold is strict: nine reachable cells are not under nine
+        state.survival_threshold = 9;
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 1)));
     }
 }
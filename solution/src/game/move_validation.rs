@@ -1,50 +1,218 @@
-use crate::types::{Player, Cell, PieceOffset};
+use crate::types::{Player, Cell, PieceOffset, CaptureRule, IllegalReason, ScanOrder};
 use super::game_state::GameState;
+use std::collections::HashMap;
 
 impl GameState {
     pub fn is_legal_move(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> bool {
         let mut own_overlaps = 0;
+        let mut captures = 0;
+        let max_captures = match self.capture_rule {
+            CaptureRule::Forbidden => 0,
+            CaptureRule::AllowOne => 1,
+        };
         let my = if self.player == Player::One { Cell::Player1 } else { Cell::Player2 };
         let op = if self.player == Player::One { Cell::Player2 } else { Cell::Player1 };
 
         for off in piece_offsets {
-            let bx = x + off.dx;
-            let by = y + off.dy;
+            let bx = x + off.dx();
+            let by = y + off.dy();
 
             // MUST be fully inside board
-            if bx < 0 || by < 0 || bx >= self.board_width as i32 || by >= self.board_height as i32 {
-                return false;
+            match self.board_get(bx, by) {
+                None => return false,
+                Some(c) if c == op => {
+                    captures += 1;
+                    if captures > max_captures {
+                        return false;
+                    }
+                }
+                Some(c) if c == my => own_overlaps += 1,
+                _ => {}
             }
-            match self.board[by as usize][bx as usize] {
-                c if c == op => return false,
-                c if c == my => own_overlaps += 1,
+        }
+        own_overlaps == self.required_overlaps()
+    }
+
+    /// Own cells a placement must cover: `required_own_overlap`, at least 1
+    pub fn required_overlaps(&self) -> usize {
+        self.required_own_overlap.max(1)
+    }
+
+    /// Most empty cells any placement of this piece can claim
+    ///
+    /// Every filled cell except the mandatory own overlap; a move reaching it
+    /// cannot be beaten on cells claimed.
+    pub fn max_claim(&self, piece_offsets: &[PieceOffset]) -> usize {
+        piece_filled_count(piece_offsets).saturating_sub(self.required_overlaps())
+    }
+
+    /// Why a placement is illegal, or `None` if it is legal
+    ///
+    /// Checks in the same order as `is_legal_move`: bounds and opponent cells
+    /// per piece cell first, then the own-overlap count.
+    pub fn diagnose_illegality(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> Option<IllegalReason> {
+        let my = self.my_cell();
+        let op = self.opponent_cell();
+        let max_captures = match self.capture_rule {
+            CaptureRule::Forbidden => 0,
+            CaptureRule::AllowOne => 1,
+        };
+        let (mut own_overlaps, mut captures) = (0, 0);
+        for off in piece_offsets {
+            match self.board_get(x + off.dx(), y + off.dy()) {
+                None => return Some(IllegalReason::OutOfBounds),
+                Some(c) if c == op => {
+                    captures += 1;
+                    if captures > max_captures {
+                        return Some(IllegalReason::OpponentOverlap);
+                    }
+                }
+                Some(c) if c == my => own_overlaps += 1,
                 _ => {}
             }
         }
-        own_overlaps == 1
+        let required = self.required_overlaps();
+        match own_overlaps {
+            n if n == required => None,
+            n if n < required => Some(IllegalReason::NoOwnOverlap),
+            _ => Some(IllegalReason::MultipleOwnOverlaps),
+        }
     }
 
-    pub fn find_legal_moves(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Vec<(i32, i32)> {
-        let mut legal = Vec::new();
+    /// Count the rejection reasons over every anchor the legal-move scan visits
+    pub fn rejection_histogram(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> HashMap<IllegalReason, usize> {
+        let mut histogram = HashMap::new();
         if self.piece_width > self.board_width || self.piece_height > self.board_height {
-            return legal;
+            return histogram;
         }
+        let end_x = self.board_width as i32 - self.piece_width as i32 + trim_off_x;
+        let end_y = self.board_height as i32 - self.piece_height as i32 + trim_off_y;
+        for y in trim_off_y..=end_y {
+            for x in trim_off_x..=end_x {
+                if let Some(reason) = self.diagnose_illegality(x, y, piece_offsets) {
+                    *histogram.entry(reason).or_insert(0) += 1;
+                }
+            }
+        }
+        histogram
+    }
 
+    pub fn find_legal_moves(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Vec<(i32, i32)> {
+        self.legal_moves_iter(piece_offsets, trim_off_x, trim_off_y).collect()
+    }
+
+    /// Number of legal placements, without collecting them
+    pub fn legal_move_count(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> usize {
+        self.legal_moves_iter(piece_offsets, trim_off_x, trim_off_y).count()
+    }
+
+    /// Legal trimmed anchors in scan order, found lazily so callers can stop early
+    ///
+    /// When every legal placement must claim an empty cell and there are fewer
+    /// (empty cell, piece cell) pairs than anchors, only the anchors that put a
+    /// piece cell on an entry of `empty_cells` are tried.
+    pub(crate) fn legal_moves_iter<'a>(&'a self, piece_offsets: &'a [PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Box<dyn Iterator<Item = (i32, i32)> + 'a> {
         // x,y here are **TRIMMED** top-lefts. We later print (x - trim_off_x, y - trim_off_y).
         // To guarantee non-negative printed coords, start scan at those offsets.
         let start_x = trim_off_x;
         let start_y = trim_off_y;
         let end_x = self.board_width as i32 - self.piece_width as i32 + trim_off_x;
+        // (both ranges are empty when the piece is larger than the board)
         let end_y = self.board_height as i32 - self.piece_height as i32 + trim_off_y;
 
-        for y in start_y..=end_y {
-            for x in start_x..=end_x {
-                if self.is_legal_move(x, y, piece_offsets) {
-                    legal.push((x, y));
-                }
-            }
+        let anchor_count = (end_x - start_x + 1).max(0) as usize * (end_y - start_y + 1).max(0) as usize;
+        if self.empty_cells.len() * piece_offsets.len() < anchor_count && self.every_placement_claims(piece_offsets) {
+            let mut anchors: Vec<(i32, i32)> = self
+                .empty_cells
+                .iter()
+                .flat_map(|&(ex, ey)| piece_offsets.iter().map(move |off| (ex as i32 - off.dx(), ey as i32 - off.dy())))
+                .filter(|&(x, y)| x >= start_x && x <= end_x && y >= start_y && y <= end_y)
+                .collect();
+            self.sort_in_scan_order(&mut anchors);
+            anchors.dedup();
+            return Box::new(anchors.into_iter().filter(move |&(x, y)| self.is_legal_move(x, y, piece_offsets)));
+        }
+
+        let anchors: Box<dyn Iterator<Item = (i32, i32)>> = match self.scan_order {
+            ScanOrder::RowMajor => Box::new((start_y..=end_y).flat_map(move |y| (start_x..=end_x).map(move |x| (x, y)))),
+            ScanOrder::ColumnMajor => Box::new((start_x..=end_x).flat_map(move |x| (start_y..=end_y).map(move |y| (x, y)))),
+        };
+        Box::new(anchors.filter(move |&(x, y)| self.is_legal_move(x, y, piece_offsets)))
+    }
+
+    /// Sort anchors into the order the legal-move scan visits them
+    fn sort_in_scan_order(&self, anchors: &mut [(i32, i32)]) {
+        match self.scan_order {
+            ScanOrder::RowMajor => anchors.sort_by_key(|&(x, y)| (y, x)),
+            ScanOrder::ColumnMajor => anchors.sort_unstable(),
+        }
+    }
+
+    /// Whether a piece has more cells than a placement may put on occupied ones
+    fn every_placement_claims(&self, piece_offsets: &[PieceOffset]) -> bool {
+        let max_captures = match self.capture_rule {
+            CaptureRule::Forbidden => 0,
+            CaptureRule::AllowOne => 1,
+        };
+        piece_filled_count(piece_offsets) > self.required_overlaps() + max_captures
+    }
+
+    /// Legal (trimmed) anchors whose footprint covers the target cell
+    ///
+    /// Only anchors `find_legal_moves` would scan are returned, in the same
+    /// order.
+    pub fn placements_covering(&self, target: (usize, usize), piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Vec<(i32, i32)> {
+        if self.piece_width > self.board_width || self.piece_height > self.board_height {
+            return Vec::new();
+        }
+        let end_x = self.board_width as i32 - self.piece_width as i32 + trim_off_x;
+        let end_y = self.board_height as i32 - self.piece_height as i32 + trim_off_y;
+        let (tx, ty) = (target.0 as i32, target.1 as i32);
+
+        let mut anchors: Vec<(i32, i32)> = piece_offsets
+            .iter()
+            .map(|off| (tx - off.dx(), ty - off.dy()))
+            .filter(|&(x, y)| x >= trim_off_x && x <= end_x && y >= trim_off_y && y <= end_y)
+            .filter(|&(x, y)| self.is_legal_move(x, y, piece_offsets))
+            .collect();
+        self.sort_in_scan_order(&mut anchors);
+        anchors
+    }
+
+    /// Whether neither side can place: the game is decided
+    ///
+    /// We need a legal placement of our piece anywhere on the board; for the
+    /// opponent, whose next piece is unknown, a sample piece stands in.
+    pub fn is_terminal(&self, my_offsets: &[PieceOffset], opp_sample_offsets: &[PieceOffset]) -> bool {
+        let width = my_offsets.iter().map(|o| o.dx() + 1).max().unwrap_or(0);
+        let height = my_offsets.iter().map(|o| o.dy() + 1).max().unwrap_or(0);
+        let we_can_move = (0..=self.board_height as i32 - height)
+            .any(|y| (0..=self.board_width as i32 - width).any(|x| self.is_legal_move(x, y, my_offsets)));
+        !we_can_move && self.opponent_legal_move_count_on(&self.board, opp_sample_offsets) == 0
+    }
+
+    /// Thin a candidate list down to `max_candidates`, nearest our territory first
+    ///
+    /// Candidates are ranked by how far their cells lie from the centroid of
+    /// our cells, so long reaches out of a tendril are dropped before moves
+    /// hugging the bulk of our territory. Ties keep scan order, and the kept
+    /// candidates come back in scan order.
+    pub fn cap_candidates(&self, moves: Vec<(i32, i32)>, piece_offsets: &[PieceOffset]) -> Vec<(i32, i32)> {
+        if self.max_candidates == 0 || moves.len() <= self.max_candidates {
+            return moves;
         }
-        legal
+        let (cx, cy) = self.my_centroid().unwrap_or((0.0, 0.0));
+        let spread = |&(x, y): &(i32, i32)| -> f32 {
+            piece_offsets
+                .iter()
+                .map(|off| ((x + off.dx()) as f32 - cx).abs() + ((y + off.dy()) as f32 - cy).abs())
+                .sum()
+        };
+        let mut ranked: Vec<(usize, f32)> = moves.iter().map(spread).enumerate().collect();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+        ranked.truncate(self.max_candidates);
+        ranked.sort_by_key(|&(idx, _)| idx);
+        ranked.into_iter().map(|(idx, _)| moves[idx]).collect()
     }
 
     /// EMERGENCY MOVE SEARCH: Exhaustive search when normal search fails
@@ -64,4 +232,241 @@ impl GameState {
         }
         moves
     }
+
+    /// Count the empty cells a placement would claim
+    pub fn count_claimed_cells(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> usize {
+        piece_offsets
+            .iter()
+            .filter(|off| self.board_get(x + off.dx(), y + off.dy()) == Some(Cell::Empty))
+            .count()
+    }
+
+    /// Count opponent cells orthogonally adjacent to the cells a placement would claim
+    ///
+    /// An opponent cell touching several claimed cells counts once per contact.
+    pub fn count_opponent_contacts(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> usize {
+        let op = self.opponent_cell();
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        piece_offsets
+            .iter()
+            .map(|off| (x + off.dx(), y + off.dy()))
+            .filter(|&(bx, by)| self.board_get(bx, by) == Some(Cell::Empty))
+            .map(|(bx, by)| {
+                directions
+                    .iter()
+                    .filter(|(dx, dy)| self.board_get(bx + dx, by + dy) == Some(op))
+                    .count()
+            })
+            .sum()
+    }
+}
+
+/// Filled cells of a trimmed piece (one offset per cell)
+pub fn piece_filled_count(offsets: &[PieceOffset]) -> usize {
+    offsets.len()
+}
+
+/// Distinct quarter-turn rotations of a trimmed piece, the piece itself first
+///
+/// Each rotation turns the previous one 90° clockwise and is trimmed and in
+/// row-major order like `trim_piece` output. Symmetric pieces yield fewer than
+/// four (a square just one).
+pub fn piece_orientations(offsets: &[PieceOffset]) -> Vec<Vec<PieceOffset>> {
+    let mut orientations: Vec<Vec<PieceOffset>> = vec![offsets.to_vec()];
+    let mut current = offsets.to_vec();
+    for _ in 0..3 {
+        let height = current.iter().map(|o| o.dy() + 1).max().unwrap_or(0);
+        let mut rotated: Vec<PieceOffset> = current
+            .iter()
+            .map(|o| PieceOffset::new(height - 1 - o.dy(), o.dx()))
+            .collect();
+        rotated.sort_by_key(|o| (o.dy(), o.dx()));
+        if !orientations.contains(&rotated) {
+            orientations.push(rotated.clone());
+        }
+        current = rotated;
+    }
+    orientations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::Grid;
+    use crate::game::testing::{frame, frame_as, random_rows};
+
+    #[test]
+    fn empty_cell_scan_finds_the_same_moves_as_the_full_scan() {
+        for seed in 0..40 {
+            // Sparse boards take the empty-cell path, dense ones the full scan
+            let empty_percent = if seed % 2 == 0 { 15 } else { 60 };
+            let rows = random_rows(seed, 12, 10, empty_percent);
+            let (mut state, offsets, trim_x, trim_y) = frame(&rows, &["...", ".##", ".#."]);
+            let mut full_scan = state.emergency_move_search(&offsets, trim_x, trim_y);
+            assert_eq!(state.find_legal_moves(&offsets, trim_x, trim_y), full_scan, "seed {}", seed);
+
+            state.scan_order = ScanOrder::ColumnMajor;
+            full_scan.sort_unstable();
+            assert_eq!(state.find_legal_moves(&offsets, trim_x, trim_y), full_scan, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn empty_cells_follow_placements() {
+        let rows = random_rows(7, 12, 10, 70);
+        let (mut state, offsets, trim_x, trim_y) = frame(&rows, &["##", "#."]);
+        for _ in 0..6 {
+            let (x, y) = state.find_legal_moves(&offsets, trim_x, trim_y)[0];
+            state.apply_move(x, y, &offsets);
+
+            let expected: std::collections::HashSet<(usize, usize)> = state
+                .board
+                .cells()
+                .filter(|&(_, _, cell)| cell == Cell::Empty)
+                .map(|(x, y, _)| (x, y))
+                .collect();
+            assert_eq!(state.empty_cells, expected);
+            assert_eq!(state.find_legal_moves(&offsets, trim_x, trim_y), state.emergency_move_search(&offsets, trim_x, trim_y));
+        }
+    }
+
+    #[test]
+    fn capture_rules_on_a_board_with_a_capture() {
+        let (mut state, offsets, tx, ty) = frame(&["@$..", "....", "...."], &["###"]);
+        assert!(!state.is_legal_move(0, 0, &offsets));
+        assert_eq!(state.diagnose_illegality(0, 0, &offsets), Some(IllegalReason::OpponentOverlap));
+        assert!(state.find_legal_moves(&offsets, tx, ty).is_empty());
+
+        state.capture_rule = CaptureRule::AllowOne;
+        assert!(state.is_legal_move(0, 0, &offsets));
+        assert_eq!(state.find_legal_moves(&offsets, tx, ty), vec![(0, 0)]);
+        assert_eq!(state.simulate_move(0, 0, &offsets).at(1, 0), Cell::Player1);
+
+        // Never more than one
+        let (mut state, offsets, _, _) = frame(&["@$$.", "....", "...."], &["###"]);
+        state.capture_rule = CaptureRule::AllowOne;
+        assert!(!state.is_legal_move(0, 0, &offsets));
+    }
+
+    #[test]
+    fn candidate_cap_keeps_the_moves_nearest_our_territory() {
+        // A single cell lands on any of our 40 * 10 cells in the top half
+        let mut rows = vec!["@".repeat(40); 10];
+        rows.extend(vec![".".repeat(40); 9]);
+        rows.push(format!("{}$", ".".repeat(39)));
+        let (mut state, offsets, tx, ty) = frame(&rows, &["#"]);
+        let legal = state.find_legal_moves(&offsets, tx, ty);
+        assert_eq!(legal.len(), 400);
+
+        state.max_candidates = 16;
+        let capped = state.cap_candidates(legal.clone(), &offsets);
+        assert_eq!(capped.len(), 16);
+        assert!(capped.iter().all(|mv| legal.contains(mv)));
+        assert!(capped.windows(2).all(|pair| (pair[0].1, pair[0].0) < (pair[1].1, pair[1].0)));
+        // Clustered around the middle of our block (centroid 19.5, 4.5), not spread over it
+        assert!(capped.iter().all(|&(x, y)| (x - 19).abs() + (y - 4).abs() <= 3), "{:?}", capped);
+
+        state.max_candidates = 0;
+        assert_eq!(state.cap_candidates(legal.clone(), &offsets), legal);
+    }
+
+    #[test]
+    fn flipped_symbols_show_up_as_opponent_overlaps() {
+        // Our territory covers most of the board; read as player two, it all looks like theirs
+        let rows = ["@@@@@@@@", "@@@@@@@@", "@@@@@@@@", "@@@@....", "@@@@...$"];
+        let (right, offsets, tx, ty) = frame(&rows, &["##", "##"]);
+        let (flipped, _, _, _) = frame_as(2, &rows, &["##", "##"]);
+
+        let histogram = flipped.rejection_histogram(&offsets, tx, ty);
+        let rejected: usize = histogram.values().sum();
+        let overlaps = histogram[&IllegalReason::OpponentOverlap];
+        assert!(overlaps * 2 > rejected, "{:?}", histogram);
+        assert!(histogram.values().all(|&n| n <= overlaps));
+
+        let histogram = right.rejection_histogram(&offsets, tx, ty);
+        assert_eq!(histogram.get(&IllegalReason::OpponentOverlap), Some(&1));
+        assert_eq!(flipped.diagnose_illegality(0, 0, &offsets), Some(IllegalReason::OpponentOverlap));
+        assert_eq!(right.diagnose_illegality(0, 0, &offsets), Some(IllegalReason::MultipleOwnOverlaps));
+    }
+
+    #[test]
+    fn l_piece_placements_covering_a_contested_cell() {
+        let rows = ["......", "......", "..@...", "....@.", "......", ".....$"];
+        let (state, offsets, tx, ty) = frame(&rows, &["#.", "##"]);
+        // The bottom-right cell on (3, 2) growing from (2, 2), or the top cell growing from (4, 3)
+        assert_eq!(state.placements_covering((3, 2), &offsets, tx, ty), vec![(2, 1), (3, 2)]);
+
+        for target in [(3, 2), (2, 3), (5, 5), (0, 0)].iter() {
+            let brute: Vec<(i32, i32)> = state.find_legal_moves(&offsets, tx, ty)
+                .into_iter()
+                .filter(|&(x, y)| offsets.iter().any(|o| (x + o.dx(), y + o.dy()) == (target.0 as i32, target.1 as i32)))
+                .collect();
+            assert_eq!(state.placements_covering(*target, &offsets, tx, ty), brute, "{:?}", target);
+        }
+    }
+
+    #[test]
+    fn overlap_requirement_defaults_to_one_and_can_be_raised() {
+        // Two seeds side by side, as a quirky engine might hand out
+        let (mut state, offsets, tx, ty) = frame(&["......", ".@@...", "......", ".....$"], &["###"]);
+        assert_eq!(state.find_legal_moves(&offsets, tx, ty), vec![(2, 1)]);
+        assert_eq!(state.diagnose_illegality(1, 1, &offsets), Some(IllegalReason::MultipleOwnOverlaps));
+
+        // Variant: the piece must cover both seeds
+        state.required_own_overlap = 2;
+        assert_eq!(state.find_legal_moves(&offsets, tx, ty), vec![(0, 1), (1, 1)]);
+        assert_eq!(state.diagnose_illegality(2, 1, &offsets), Some(IllegalReason::NoOwnOverlap));
+
+        // Zero is clamped back to the standard rule
+        state.required_own_overlap = 0;
+        assert_eq!(state.find_legal_moves(&offsets, tx, ty), vec![(2, 1)]);
+    }
+
+    #[test]
+    fn filled_count_matches_the_offsets_and_caps_the_claim() {
+        let rows = ["......", ".@....", "......", ".....$"];
+        for piece in [&["#"][..], &["##"], &[".#.", "###", ".#."], &["#..", "...", "..#"]] {
+            let (state, offsets, _, _) = frame(&rows, piece);
+            let filled = piece.iter().map(|row| row.matches('#').count()).sum::<usize>();
+            assert_eq!(piece_filled_count(&offsets), offsets.len());
+            assert_eq!(piece_filled_count(&offsets), filled);
+            assert_eq!(state.max_claim(&offsets), filled - 1);
+        }
+
+        // No legal placement of an L tromino claims more than the ceiling
+        let (mut state, offsets, tx, ty) = frame(&rows, &["#.", "##"]);
+        let best = state.find_legal_moves(&offsets, tx, ty).into_iter().map(|(x, y)| state.count_claimed_cells(x, y, &offsets)).max();
+        assert_eq!(best, Some(state.max_claim(&offsets)));
+        state.required_own_overlap = 2;
+        assert_eq!(state.max_claim(&offsets), 1);
+    }
+
+    #[test]
+    fn partitioned_board_is_terminal_for_both_sides() {
+        let domino = [PieceOffset::new(0, 0), PieceOffset::new(1, 0)];
+        let (state, offsets, _, _) = frame(&["@@$$", "@@$$", "@@$$"], &["##"]);
+        assert!(state.is_terminal(&offsets, &domino));
+
+        // One empty cell beside either side keeps the game going
+        let (state, offsets, _, _) = frame(&["@@$.", "@@$$", "@@$$"], &["##"]);
+        assert!(!state.is_terminal(&offsets, &domino));
+        let (state, offsets, _, _) = frame(&["@@$$", "@.$$", "@@$$"], &["##"]);
+        assert!(!state.is_terminal(&offsets, &domino));
+    }
+
+    #[test]
+    fn legal_move_count_matches_the_collected_moves() {
+        let pieces: [&[&str]; 3] = [&["#"], &["##", "#."], &["...", ".##", ".#."]];
+        let mut nonzero = 0;
+        for seed in 0..30 {
+            for (i, piece) in pieces.iter().enumerate() {
+                let rows = random_rows(seed, 11, 9, 40 + 15 * i as u64);
+                let (state, offsets, trim_x, trim_y) = frame_as(1 + (seed % 2) as u8, &rows, piece);
+                let count = state.legal_move_count(&offsets, trim_x, trim_y);
+                assert_eq!(count, state.find_legal_moves(&offsets, trim_x, trim_y).len(), "seed {} piece {}", seed, i);
+                nonzero += (count > 0) as usize;
+            }
+        }
+        assert!(nonzero > 60, "only {} boards had moves", nonzero);
+    }
 }
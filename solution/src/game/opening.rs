@@ -0,0 +1,72 @@
+use crate::types::{Cell, PieceOffset, ScoredMove};
+use super::game_state::{to_board_index, GameState};
+use super::board::Grid;
+use std::cmp::Ordering;
+
+impl GameState {
+    /// Check whether we are still on our starting seed
+    pub fn is_opening(&self) -> bool {
+        self.count_my_territory() <= 1
+    }
+
+    /// Opening placement: grow away from the opponent's seed, staying central
+    ///
+    /// The first placement has nothing to connect to and sets up the rest of
+    /// the game, so instead of the general scorer we take the legal placement
+    /// whose closest claimed cell is farthest from the opponent, breaking ties
+    /// toward the board center and then top-left. Returns engine coordinates.
+    pub fn opening_move(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32)> {
+        let distance_map = self.calculate_distance_map();
+        let mut best: Option<(i32, i32, ScoredMove)> = None;
+
+        for (x, y) in self.find_legal_moves(piece_offsets, trim_off_x, trim_off_y) {
+            let min_dist = piece_offsets
+                .iter()
+                .filter_map(|off| {
+                    let (bx, by) = to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height)?;
+                    if self.board.at(bx, by) == Cell::Empty { Some(distance_map[by][bx]) } else { None }
+                })
+                .min()
+                .unwrap_or(0);
+            let candidate = ScoredMove::new(x, y, 0);
+            let center = self.center_distance(&candidate);
+
+            let is_better = match &best {
+                None => true,
+                Some((best_dist, best_center, best_move)) => match min_dist.cmp(best_dist) {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => center < *best_center
+                        || (center == *best_center && candidate.cmp(best_move) == Ordering::Greater),
+                },
+            };
+            if is_better {
+                best = Some((min_dist, center, candidate));
+            }
+        }
+
+        // Convert TRIMMED anchor → ORIGINAL top-left for the engine
+        best.map(|(_, _, m)| ((m.x - trim_off_x).max(0), (m.y - trim_off_y).max(0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::testing::frame;
+
+    #[test]
+    fn opening_grows_away_from_the_opponent() {
+        let mut rows = vec![".".repeat(15); 15];
+        rows[3] = "...@...........".to_string();
+        rows[11] = "...........$...".to_string();
+        let (state, offsets, tx, ty) = frame(&rows, &["##", "#."]);
+        assert!(state.is_opening());
+
+        let (x, y) = state.best_move(&offsets, tx, ty).unwrap();
+        assert_eq!(state.opening_move(&offsets, tx, ty), Some((x, y)));
+        // No claimed cell steps past our seed toward the opponent
+        for off in &offsets {
+            assert!(x + off.dx() + y + off.dy() <= 6, "{:?}", (x, y));
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::types::{Axis, Cell, PieceOffset};
+use super::game_state::{to_board_index, GameState};
+use super::board::Grid;
+
+/// How far past our frontier we look for open space in each direction
+const OPEN_RUN_CAP: i32 = 8;
+
+/// Long axis of a piece's bounding box (`Axis::None` for square boxes)
+pub fn piece_principal_axis(offsets: &[PieceOffset]) -> Axis {
+    let (width, height) = piece_extent(offsets);
+    if width > height {
+        Axis::Horizontal
+    } else if height > width {
+        Axis::Vertical
+    } else {
+        Axis::None
+    }
+}
+
+/// Width and height of a piece's bounding box
+fn piece_extent(offsets: &[PieceOffset]) -> (i32, i32) {
+    if offsets.is_empty() {
+        return (0, 0);
+    }
+    let min_x = offsets.iter().map(|o| o.dx()).min().unwrap_or(0);
+    let max_x = offsets.iter().map(|o| o.dx()).max().unwrap_or(0);
+    let min_y = offsets.iter().map(|o| o.dy()).min().unwrap_or(0);
+    let max_y = offsets.iter().map(|o| o.dy()).max().unwrap_or(0);
+    (max_x - min_x + 1, max_y - min_y + 1)
+}
+
+impl GameState {
+    /// Direction in which a cell faces the most open space
+    ///
+    /// Compares the straight runs of empty cells (capped) leading away from
+    /// the cell horizontally and vertically.
+    pub fn open_space_axis(&self, x: usize, y: usize) -> Axis {
+        let horizontal = self.open_run(x, y, 1, 0) + self.open_run(x, y, -1, 0);
+        let vertical = self.open_run(x, y, 0, 1) + self.open_run(x, y, 0, -1);
+        if horizontal > vertical {
+            Axis::Horizontal
+        } else if vertical > horizontal {
+            Axis::Vertical
+        } else {
+            Axis::None
+        }
+    }
+
+    /// Bonus for an elongated piece growing along the open space at its anchor
+    ///
+    /// The open-space direction is taken at the cell of ours the placement
+    /// overlaps (where it grows from). Worth how much longer than wide the
+    /// piece is, so 1×4 bars gain most.
+    pub fn orientation_alignment(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        let axis = piece_principal_axis(piece_offsets);
+        if axis == Axis::None {
+            return 0;
+        }
+        let my = self.my_cell();
+        let root = piece_offsets
+            .iter()
+            .filter_map(|off| to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height))
+            .find(|&(cx, cy)| self.board.at(cx, cy) == my);
+        match root {
+            Some((cx, cy)) if self.open_space_axis(cx, cy) == axis => {}
+            _ => return 0,
+        }
+        let (width, height) = piece_extent(piece_offsets);
+        (width - height).abs()
+    }
+
+    /// Empty cells in a straight line from (x, y), not counting the start
+    fn open_run(&self, x: usize, y: usize, dx: i32, dy: i32) -> i32 {
+        let mut run = 0;
+        let (mut cx, mut cy) = (x as i32 + dx, y as i32 + dy);
+        while run < OPEN_RUN_CAP && self.board_get(cx, cy) == Some(Cell::Empty) {
+            run += 1;
+            cx += dx;
+            cy += dy;
+        }
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::frame;
+
+    #[test]
+    fn bar_along_the_open_side_earns_the_orientation_bonus() {
+        // Nine open cells to the right of our cell, four above and below
+        let rows = ["..........", "..........", "@.........", "..........", ".........$"];
+        let (state, across, _, _) = frame(&rows, &["####"]);
+        let down: Vec<PieceOffset> = (0..4).map(|dy| PieceOffset::new(0, dy)).collect();
+        assert_eq!(state.open_space_axis(0, 2), Axis::Horizontal);
+        assert_eq!(piece_principal_axis(&down), Axis::Vertical);
+
+        // A 1x4 bar is three cells longer than wide
+        assert!(state.is_legal_move(0, 2, &across) && state.is_legal_move(0, 0, &down));
+        assert_eq!(state.orientation_alignment(0, 2, &across), 3);
+        assert_eq!(state.orientation_alignment(0, 0, &down), 0);
+        assert_eq!(state.orientation_alignment(0, 2, &[PieceOffset::new(0, 0)]), 0);
+    }
+}
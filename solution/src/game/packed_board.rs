@@ -0,0 +1,96 @@
+use crate::types::Cell;
+
+/// Cells stored per byte (2 bits each)
+const CELLS_PER_BYTE: usize = 4;
+
+/// Board with each cell packed into 2 bits, four cells per byte
+///
+/// A quarter of the memory of `Vec<Vec<Cell>>` (and no per-row allocations),
+/// for very large boards. With the `packed-board` feature this is the game
+/// state's only board (see `Board`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedBoard {
+    width: usize,
+    height: usize,
+    bytes: Vec<u8>,
+}
+
+impl PackedBoard {
+    /// All-empty board of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        let cells = width * height;
+        PackedBoard {
+            width,
+            height,
+            bytes: vec![0; cells.div_ceil(CELLS_PER_BYTE)],
+        }
+    }
+
+    /// Pack an unpacked board
+    pub fn from_rows(rows: &[Vec<Cell>]) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut packed = PackedBoard::new(width, height);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                packed.set(x, y, cell);
+            }
+        }
+        packed
+    }
+
+    /// Unpack into rows of cells
+    pub fn to_rows(&self) -> Vec<Vec<Cell>> {
+        (0..self.height)
+            .map(|y| (0..self.width).map(|x| self.get(x, y)).collect())
+            .collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Cell at (x, y); callers must stay within the board
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        let (byte, shift) = self.slot(x, y);
+        match (self.bytes[byte] >> shift) & 0b11 {
+            1 => Cell::Player1,
+            2 => Cell::Player2,
+            _ => Cell::Empty,
+        }
+    }
+
+    /// Overwrite the cell at (x, y); callers must stay within the board
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        let (byte, shift) = self.slot(x, y);
+        let bits = match cell {
+            Cell::Empty => 0,
+            Cell::Player1 => 1,
+            Cell::Player2 => 2,
+        };
+        self.bytes[byte] = (self.bytes[byte] & !(0b11 << shift)) | (bits << shift);
+    }
+
+    /// Drop every row from `height` on
+    pub fn truncate_height(&mut self, height: usize) {
+        if height < self.height {
+            let cells = self.width * height;
+            self.height = height;
+            self.bytes.truncate(cells.div_ceil(CELLS_PER_BYTE));
+            // Clear the dropped cells sharing the last byte, so equal boards compare equal
+            if let (Some(last), used @ 1..) = (self.bytes.last_mut(), cells % CELLS_PER_BYTE) {
+                *last &= (1u8 << (used * 2)) - 1;
+            }
+        }
+    }
+
+    fn slot(&self, x: usize, y: usize) -> (usize, u32) {
+        debug_assert!(x < self.width && y < self.height);
+        let index = y * self.width + x;
+        (index / CELLS_PER_BYTE, ((index % CELLS_PER_BYTE) * 2) as u32)
+    }
+}
@@ -0,0 +1,314 @@
+use crate::types::{Cell, Player, TieBreak};
+use super::game_state::GameState;
+use super::board::{Board, Grid};
+use std::io;
+
+/// Leading bytes of a serialized game state
+const MAGIC: &[u8; 4] = b"FLST";
+/// Bumped whenever the layout below changes
+const FORMAT_VERSION: u8 = 3;
+
+impl GameState {
+    /// Serialize the board, player, symbols, dimensions and scoring knobs
+    ///
+    /// Little-endian, with every variable-length block prefixed by its length.
+    /// Piece state is transient and is not stored.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(FORMAT_VERSION);
+
+        out.push(match self.player {
+            Player::One => 1,
+            Player::Two => 2,
+        });
+        for ch in &[self.my_symbols.0, self.my_symbols.1, self.opponent_symbols.0, self.opponent_symbols.1] {
+            put_u32(&mut out, *ch as u32);
+        }
+
+        put_u32(&mut out, self.board_width as u32);
+        put_u32(&mut out, self.board_height as u32);
+        put_u32(&mut out, (self.board_width * self.board_height) as u32);
+        for (_, _, cell) in self.board.cells() {
+            out.push(match cell {
+                Cell::Empty => 0,
+                Cell::Player1 => 1,
+                Cell::Player2 => 2,
+            });
+        }
+
+        let weights = self.weight_fields();
+        put_u32(&mut out, weights.len() as u32);
+        for w in weights {
+            out.extend_from_slice(&w.to_le_bytes());
+        }
+        out.extend_from_slice(&self.early_phase_end.to_le_bytes());
+        out.extend_from_slice(&self.late_phase_start.to_le_bytes());
+        out.push(match self.tie_break {
+            TieBreak::TopLeft => 0,
+            TieBreak::CenterBias => 1,
+        });
+        put_u32(&mut out, self.aggression_curve.len() as u32);
+        for &(progress, multiplier) in &self.aggression_curve {
+            out.extend_from_slice(&progress.to_le_bytes());
+            out.extend_from_slice(&multiplier.to_le_bytes());
+        }
+        out.extend_from_slice(&(self.node_budget as u64).to_le_bytes());
+        // 0 stands for no reach limit
+        out.extend_from_slice(&self.max_reach_radius.unwrap_or(0).to_le_bytes());
+
+        out
+    }
+
+    /// Restore a game state written by `to_bytes`
+    pub fn from_bytes(data: &[u8]) -> io::Result<GameState> {
+        let mut reader = ByteReader { data, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(invalid("not a serialized game state"));
+        }
+        if reader.u8()? != FORMAT_VERSION {
+            return Err(invalid("unsupported game state version"));
+        }
+
+        let mut state = GameState::new();
+        state.player = match reader.u8()? {
+            1 => Player::One,
+            2 => Player::Two,
+            _ => return Err(invalid("bad player")),
+        };
+        state.player_known = true;
+        state.my_symbols = (reader.char()?, reader.char()?);
+        state.opponent_symbols = (reader.char()?, reader.char()?);
+
+        state.board_width = reader.u32()? as usize;
+        state.board_height = reader.u32()? as usize;
+        let dims = 1..=state.max_board_dim;
+        if !dims.contains(&state.board_width) || !dims.contains(&state.board_height) {
+            return Err(invalid("board dimensions out of range"));
+        }
+        let cell_count = reader.u32()? as usize;
+        if cell_count != state.board_width * state.board_height {
+            return Err(invalid("board length does not match dimensions"));
+        }
+        let cells = reader.take(cell_count)?;
+        state.board = Board::empty(state.board_width, state.board_height);
+        for (i, &byte) in cells.iter().enumerate() {
+            let cell = match byte {
+                0 => Cell::Empty,
+                1 => Cell::Player1,
+                2 => Cell::Player2,
+                _ => return Err(invalid("bad board cell")),
+            };
+            state.board.put(i % state.board_width, i / state.board_width, cell);
+        }
+        state.sync_empty_cells();
+
+        let weight_count = reader.u32()? as usize;
+        let mut weights = Vec::with_capacity(weight_count);
+        for _ in 0..weight_count {
+            weights.push(reader.i32()?);
+        }
+        state.set_weight_fields(&weights)?;
+        state.early_phase_end = reader.f32()?;
+        state.late_phase_start = reader.f32()?;
+        state.tie_break = match reader.u8()? {
+            0 => TieBreak::TopLeft,
+            1 => TieBreak::CenterBias,
+            _ => return Err(invalid("bad tie-break")),
+        };
+        let curve_len = reader.u32()? as usize;
+        let mut curve = Vec::new();
+        for _ in 0..curve_len {
+            curve.push((reader.f32()?, reader.f32()?));
+        }
+        state.aggression_curve = curve;
+        state.node_budget = reader.u64()? as usize;
+        let radius = reader.i32()?;
+        state.max_reach_radius = if radius > 0 { Some(radius) } else { None };
+
+        Ok(state)
+    }
+
+    /// Integer scoring knobs in serialization order
+    fn weight_fields(&self) -> Vec<i32> {
+        vec![
+            self.heat_weight,
+            self.expansion_weight,
+            self.blocking_weight,
+            self.compactness_weight,
+            self.frontier_weight,
+            self.path_cost_weight,
+            self.fill_ratio_weight,
+            self.overlap_frontier_weight,
+            self.frontier_length_weight,
+            self.mirror_weight,
+            self.safe_cell_weight,
+            self.centrality_weight,
+            self.anchor_region_weight,
+            self.mobility_weight,
+            self.mobility_floor,
+            self.unlock_weight,
+            self.hole_weight,
+            self.region_capture_weight,
+            self.orientation_weight,
+            self.contested_frontier_weight,
+            self.wall_lead_percent,
+            self.seed_barrier_weight,
+            self.seed_barrier_turns,
+            self.exposed_frontier_weight,
+            self.frontier_openness_weight,
+            self.interior_overlap_weight,
+            self.connectivity_weight,
+            self.losing_score_floor,
+        ]
+    }
+
+    fn set_weight_fields(&mut self, weights: &[i32]) -> io::Result<()> {
+        let mut fields = [
+            &mut self.heat_weight,
+            &mut self.expansion_weight,
+            &mut self.blocking_weight,
+            &mut self.compactness_weight,
+            &mut self.frontier_weight,
+            &mut self.path_cost_weight,
+            &mut self.fill_ratio_weight,
+            &mut self.overlap_frontier_weight,
+            &mut self.frontier_length_weight,
+            &mut self.mirror_weight,
+            &mut self.safe_cell_weight,
+            &mut self.centrality_weight,
+            &mut self.anchor_region_weight,
+            &mut self.mobility_weight,
+            &mut self.mobility_floor,
+            &mut self.unlock_weight,
+            &mut self.hole_weight,
+            &mut self.region_capture_weight,
+            &mut self.orientation_weight,
+            &mut self.contested_frontier_weight,
+            &mut self.wall_lead_percent,
+            &mut self.seed_barrier_weight,
+            &mut self.seed_barrier_turns,
+            &mut self.exposed_frontier_weight,
+            &mut self.frontier_openness_weight,
+            &mut self.interior_overlap_weight,
+            &mut self.connectivity_weight,
+            &mut self.losing_score_floor,
+        ];
+        if weights.len() != fields.len() {
+            return Err(invalid("unexpected number of weights"));
+        }
+        for (field, &value) in fields.iter_mut().zip(weights) {
+            **field = value;
+        }
+        Ok(())
+    }
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Cursor over a byte slice that reports truncation as `UnexpectedEof`
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() - self.pos < len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated game state"));
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    fn char(&mut self) -> io::Result<char> {
+        std::char::from_u32(self.u32()?).ok_or_else(|| invalid("bad symbol"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_state() -> GameState {
+        let frame = "$$$ exec p2 : [bot]\nAnfield 5 3:\n    01234\n000 @....\n001 ..a..\n002 ....$\nPiece 1 1:\n#\n";
+        let mut state = GameState::new();
+        state.parse_frame(frame).unwrap();
+        state
+    }
+
+    #[test]
+    fn state_survives_a_round_trip() {
+        let mut state = sample_state();
+        state.heat_weight = 42;
+        state.early_phase_end = 0.25;
+        state.node_budget = 500;
+        let restored = GameState::from_bytes(&state.to_bytes()).unwrap();
+
+        assert_eq!(restored.player, state.player);
+        assert_eq!(restored.my_symbols, state.my_symbols);
+        assert_eq!(restored.opponent_symbols, state.opponent_symbols);
+        assert_eq!((restored.board_width, restored.board_height), (5, 3));
+        assert_eq!(restored.board.cells().collect::<Vec<_>>(), state.board.cells().collect::<Vec<_>>());
+        assert_eq!(restored.weight_fields(), state.weight_fields());
+        assert_eq!(restored.early_phase_end, 0.25);
+        assert_eq!(restored.node_budget, 500);
+        assert_eq!(restored.to_bytes(), state.to_bytes());
+    }
+
+    #[test]
+    fn out_of_range_dimensions_are_rejected() {
+        let bytes = sample_state().to_bytes();
+        // Width sits right after magic, version, player and four symbols
+        let width_at = 4 + 1 + 1 + 4 * 4;
+        for width in &[0u32, GameState::new().max_board_dim as u32 + 1, u32::MAX] {
+            let mut data = bytes.clone();
+            data[width_at..width_at + 4].copy_from_slice(&width.to_le_bytes());
+            let err = GameState::from_bytes(&data).err().unwrap();
+            assert_eq!(err.to_string(), "board dimensions out of range");
+        }
+    }
+
+    #[test]
+    fn reach_radius_survives_a_round_trip() {
+        let mut state = sample_state();
+        state.max_reach_radius = Some(7);
+        assert_eq!(GameState::from_bytes(&state.to_bytes()).unwrap().max_reach_radius, Some(7));
+
+        state.max_reach_radius = None;
+        assert_eq!(GameState::from_bytes(&state.to_bytes()).unwrap().max_reach_radius, None);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::types::{Cell, PieceOffset};
+use super::game_state::GameState;
+use super::board::{Board, Grid};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Steps through empty cells within which we count an empty cell as within our reach
+const MY_REACH_RADIUS: i32 = 4;
+
+impl GameState {
+    /// Estimate how many steps the opponent needs to reach an empty cell
+    ///
+    /// Runs A* (Manhattan heuristic) from every opponent cell, walking through
+    /// empty cells only, so our territory acts as a wall. Returns `None` when
+    /// the target is ours, off the board, or sealed off from the opponent.
+    pub fn opponent_path_cost(&self, target: (usize, usize)) -> Option<i32> {
+        let (tx, ty) = target;
+        if tx >= self.board_width || ty >= self.board_height {
+            return None;
+        }
+        let op = self.opponent_cell();
+        match self.board.at(tx, ty) {
+            c if c == op => return Some(0),
+            Cell::Empty => {}
+            _ => return None,
+        }
+
+        let heuristic = |x: usize, y: usize| (x as i32 - tx as i32).abs() + (y as i32 - ty as i32).abs();
+        let mut best_cost = vec![vec![i32::MAX; self.board_width]; self.board_height];
+        let mut open = BinaryHeap::new();
+
+        // Every opponent cell is a starting point
+        for (x, y, cell) in self.board.cells() {
+            if cell == op {
+                best_cost[y][x] = 0;
+                open.push(Reverse((heuristic(x, y), 0, x, y)));
+            }
+        }
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some(Reverse((_, cost, x, y))) = open.pop() {
+            if (x, y) == target {
+                return Some(cost);
+            }
+            if cost > best_cost[y][x] {
+                continue;
+            }
+
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if self.board.at(nx, ny) != Cell::Empty {
+                    continue;
+                }
+                let next_cost = cost + 1;
+                if next_cost < best_cost[ny][nx] {
+                    best_cost[ny][nx] = next_cost;
+                    open.push(Reverse((next_cost + heuristic(nx, ny), next_cost, nx, ny)));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Cells the opponent can reach by flooding from their territory through empty cells
+    pub fn opponent_reachability(&self) -> Vec<Vec<bool>> {
+        self.opponent_reachability_on(&self.board)
+    }
+
+    /// Same as `opponent_reachability`, on an arbitrary board
+    pub fn opponent_reachability_on(&self, board: &Board) -> Vec<Vec<bool>> {
+        let op = self.opponent_cell();
+        let mut reached = vec![vec![false; self.board_width]; self.board_height];
+        let mut queue = VecDeque::new();
+        for (x, y, cell) in board.cells() {
+            if cell == op {
+                reached[y][x] = true;
+                queue.push_back((x, y));
+            }
+        }
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if !reached[ny][nx] && board.at(nx, ny) == Cell::Empty {
+                    reached[ny][nx] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        reached
+    }
+
+    /// Empty cells the opponent can never reach (walled off by us or the board edge)
+    pub fn opponent_unreachable_empties(&self) -> Vec<(usize, usize)> {
+        let reached = self.opponent_reachability();
+        let mut safe = Vec::new();
+        for (x, y, cell) in self.board.cells() {
+            if cell == Cell::Empty && !reached[y][x] {
+                safe.push((x, y));
+            }
+        }
+        safe
+    }
+
+    /// Check whether we already hold a majority the opponent can never overturn
+    ///
+    /// Even if the opponent claimed every empty cell they can still reach, they
+    /// would end with fewer cells than we hold now.
+    pub fn is_won(&self) -> bool {
+        let op = self.opponent_cell();
+        let reached = self.opponent_reachability();
+        let opponent_ceiling = self
+            .board
+            .cells()
+            .filter(|&(x, y, cell)| cell == op || (cell == Cell::Empty && reached[y][x]))
+            .count() as i32;
+        self.count_my_territory() > opponent_ceiling
+    }
+
+    /// Empty cells the opponent could reach before this placement but not after it
+    ///
+    /// Counts the space a move walls off from them (cells the piece covers
+    /// itself are not included); those cells are effectively ours.
+    pub fn opponent_region_captured_by(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> usize {
+        self.opponent_region_captured_from(&self.opponent_reachability(), x, y, piece_offsets)
+    }
+
+    /// Same as `opponent_region_captured_by`, given the current `opponent_reachability`
+    pub fn opponent_region_captured_from(&self, reached_before: &[Vec<bool>], x: i32, y: i32, piece_offsets: &[PieceOffset]) -> usize {
+        let after = self.simulate_move(x, y, piece_offsets);
+        let reached_after = self.opponent_reachability_on(&after);
+        let mut captured = 0;
+        for (xx, yy, cell) in after.cells() {
+            if cell == Cell::Empty && reached_before[yy][xx] && !reached_after[yy][xx] {
+                captured += 1;
+            }
+        }
+        captured
+    }
+
+    /// Empty cells we can still grow into: connected to our territory through empty cells
+    pub fn reachable_empty_from_my_territory(&self) -> usize {
+        self.reachable_empty_from_my_territory_on(&self.board)
+    }
+
+    /// Same as `reachable_empty_from_my_territory`, on an arbitrary board
+    pub fn reachable_empty_from_my_territory_on(&self, board: &Board) -> usize {
+        let my = self.my_cell();
+        let mut seen = vec![vec![false; self.board_width]; self.board_height];
+        let mut queue = VecDeque::new();
+        for (x, y, cell) in board.cells() {
+            if cell == my {
+                seen[y][x] = true;
+                queue.push_back((x, y));
+            }
+        }
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let mut reached = 0;
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if !seen[ny][nx] && board.at(nx, ny) == Cell::Empty {
+                    seen[ny][nx] = true;
+                    reached += 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        reached
+    }
+
+    /// Empty cells within a few steps of our territory (walking through empty cells only)
+    pub fn my_reachability_on(&self, board: &Board) -> Vec<Vec<bool>> {
+        let my = self.my_cell();
+        let mut steps = vec![vec![-1; self.board_width]; self.board_height];
+        let mut queue = VecDeque::new();
+        for (x, y, cell) in board.cells() {
+            if cell == my {
+                steps[y][x] = 0;
+                queue.push_back((x, y));
+            }
+        }
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some((x, y)) = queue.pop_front() {
+            if steps[y][x] == MY_REACH_RADIUS {
+                continue;
+            }
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if steps[ny][nx] == -1 && board.at(nx, ny) == Cell::Empty {
+                    steps[ny][nx] = steps[y][x] + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        // Only empty cells get a positive step count
+        steps.iter().map(|row| row.iter().map(|&s| s > 0).collect()).collect()
+    }
+
+    /// Empty cells a move brings within our reach that were out of it before
+    ///
+    /// Rewards placements that open a corridor into a pocket we could not get
+    /// at, independent of how many cells the move claims itself.
+    pub fn newly_reachable_empties(&self, board_before: &Board, board_after: &Board) -> usize {
+        let before = self.my_reachability_on(board_before);
+        let after = self.my_reachability_on(board_after);
+        before
+            .iter()
+            .flatten()
+            .zip(after.iter().flatten())
+            .filter(|&(&was, &is)| is && !was)
+            .count()
+    }
+
+    /// Empty cells on a shortest path from our territory to the opponent's
+    ///
+    /// BFS through empty cells from all of our cells; the path runs from next
+    /// to us to next to them. Early on both territories are little more than
+    /// the seeds, so this is the corridor the opponent rushes along. Empty if
+    /// they cannot be reached.
+    pub fn seed_to_seed_path(&self) -> Vec<(usize, usize)> {
+        let (my, op) = (self.my_cell(), self.opponent_cell());
+        let mut parent: Vec<Vec<Option<(usize, usize)>>> = vec![vec![None; self.board_width]; self.board_height];
+        let mut seen = vec![vec![false; self.board_width]; self.board_height];
+        let mut queue = VecDeque::new();
+        for (x, y, cell) in self.board.cells() {
+            if cell == my {
+                seen[y][x] = true;
+                queue.push_back((x, y));
+            }
+        }
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if self.board.at(nx, ny) == op && self.board.at(x, y) == Cell::Empty {
+                    // Walk back from the cell touching them to the one touching us
+                    let mut path = vec![(x, y)];
+                    let mut at = (x, y);
+                    while let Some(prev) = parent[at.1][at.0] {
+                        path.push(prev);
+                        at = prev;
+                    }
+                    path.reverse();
+                    return path;
+                }
+                if !seen[ny][nx] && self.board.at(nx, ny) == Cell::Empty {
+                    seen[ny][nx] = true;
+                    if self.board.at(x, y) == Cell::Empty {
+                        parent[ny][nx] = Some((x, y));
+                    }
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::testing::{board_state, frame};
+
+    #[test]
+    fn our_wall_makes_the_far_side_expensive() {
+        let open = board_state(&["$.....", "......", "......", "......", "......"]);
+        assert_eq!(open.opponent_path_cost((3, 0)), Some(3));
+
+        let walled = board_state(&["$.@...", "..@...", "..@...", "..@...", "......"]);
+        assert_eq!(walled.opponent_path_cost((3, 0)), Some(11));
+        assert_eq!(walled.opponent_path_cost((2, 0)), None);
+        assert_eq!(walled.opponent_path_cost((0, 0)), Some(0));
+    }
+
+    #[test]
+    fn pocket_enclosed_by_our_cells_is_safe() {
+        let state = board_state(&["$......", "...@@@.", "...@.@.", "...@.@.", "...@@@."]);
+        assert_eq!(state.opponent_unreachable_empties(), vec![(4, 2), (4, 3)]);
+        let reach = state.opponent_reachability();
+        assert!(reach[0][6] && reach[2][6] && !reach[2][4]);
+    }
+
+    #[test]
+    fn pushing_down_a_corridor_brings_the_pocket_within_reach() {
+        let rows = ["@$$$$...", "@.......", "@$$$$..."];
+        let (state, offsets, _, _) = frame(&rows, &["###"]);
+        // Our reach ends where the corridor meets the pocket
+        assert!(state.my_reachability_on(&state.board)[1][4]);
+        assert!(!state.my_reachability_on(&state.board)[1][5]);
+
+        let after = state.simulate_move(0, 1, &offsets);
+        // (5, 0), (5, 1), (5, 2) and (6, 1) open up
+        assert_eq!(state.newly_reachable_empties(&state.board, &after), 4);
+        assert_eq!(state.newly_reachable_empties(&state.board, &state.board), 0);
+    }
+
+    #[test]
+    fn sealing_the_corridor_captures_the_pocket_behind_it() {
+        let rows = ["$......", "@@@.@@@", "@.....@", "@@@@@@@"];
+        let (state, offsets, _, _) = frame(&rows, &["##"]);
+        // Either anchor plugging (3, 1) walls off the five cells below
+        assert_eq!(state.opponent_region_captured_by(2, 1, &offsets), 5);
+        assert_eq!(state.opponent_region_captured_by(3, 1, &offsets), 5);
+        // Growing inside the pocket leaves the gap open
+        assert_eq!(state.opponent_region_captured_by(0, 2, &offsets), 0);
+        let reached = state.opponent_reachability();
+        assert_eq!(state.opponent_region_captured_from(&reached, 2, 1, &offsets), 5);
+    }
+
+    #[test]
+    fn won_once_the_opponent_cannot_catch_up() {
+        // Walled into the corner: at most 1 cell against our 3
+        assert!(board_state(&["$@....", "@@....", "......", "......"]).is_won());
+        // Open board: they can still reach everything
+        assert!(!board_state(&["$....@", "@@....", "......", "......"]).is_won());
+        // A tie is not a win: 2 cells plus 2 reachable against our 4
+        assert!(!board_state(&["$..@", "@@@$"]).is_won());
+        assert!(board_state(&["$.@@", "@@@$"]).is_won());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::types::{Cell, PieceOffset};
+use super::game_state::GameState;
+
+impl GameState {
+    /// Render the board in the engine's `Anfield` format
+    ///
+    /// Cells are read through `board_get`, so the output reflects whichever
+    /// board backend is active.
+    pub fn render_board(&self) -> String {
+        let mut out = format!("Anfield {} {}:\n", self.board_width, self.board_height);
+        out.push_str(&self.render_grid(|x, y| self.cell_glyph(x, y)));
+        out
+    }
+
+    /// Render the board with the cells a placement claims shown as `*`
+    ///
+    /// `x`, `y` is the trimmed anchor; the overlapped own cell keeps its symbol.
+    pub fn render_with_placement(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> String {
+        let claimed: Vec<(i32, i32)> = piece_offsets
+            .iter()
+            .map(|off| (x + off.dx(), y + off.dy()))
+            .filter(|&(cx, cy)| self.board_get(cx, cy) == Some(Cell::Empty))
+            .collect();
+        self.render_grid(|cx, cy| {
+            if claimed.contains(&(cx as i32, cy as i32)) {
+                '*'
+            } else {
+                self.cell_glyph(cx, cy)
+            }
+        })
+    }
+
+    /// Render a distance map aligned like `render_board`
+    ///
+    /// Empty cells show their distance (`0`-`9`, `+` beyond that, `.` if
+    /// unreachable); opponent cells are `X` and ours `O`.
+    pub fn render_distance_map(&self, dist: &[Vec<i32>]) -> String {
+        let (my, op) = (self.my_cell(), self.opponent_cell());
+        self.render_grid(|x, y| {
+            let d = dist.get(y).and_then(|row| row.get(x)).copied().unwrap_or(-1);
+            match self.board_get(x as i32, y as i32) {
+                Some(c) if c == op => 'X',
+                Some(c) if c == my => 'O',
+                _ if d < 0 => '.',
+                _ => std::char::from_digit(d as u32, 10).unwrap_or('+'),
+            }
+        })
+    }
+
+    /// Column header and numbered rows, one glyph per cell
+    fn render_grid(&self, glyph: impl Fn(usize, usize) -> char) -> String {
+        let mut out = String::from("    ");
+        for x in 0..self.board_width {
+            out.push(std::char::from_digit((x % 10) as u32, 10).unwrap_or('0'));
+        }
+        out.push('\n');
+
+        for y in 0..self.board_height {
+            out.push_str(&format!("{:03} ", y));
+            for x in 0..self.board_width {
+                out.push(glyph(x, y));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn cell_glyph(&self, x: usize, y: usize) -> char {
+        match self.board_get(x as i32, y as i32) {
+            Some(Cell::Player1) => '@',
+            Some(Cell::Player2) => '$',
+            _ => '.',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::testing::{board_state, frame};
+
+    #[test]
+    fn distance_map_renders_in_single_char_buckets() {
+        let state = board_state(&["$.......@...", "............"]);
+        let expected = "    012345678901\n\
+                        000 X1234567O9++\n\
+                        001 123456789+++\n";
+        assert_eq!(state.render_distance_map(&state.calculate_distance_map()), expected);
+
+        // Cells no opponent cell reaches (-1) stay blank
+        let unreached = vec![vec![-1; 12]; 2];
+        assert_eq!(state.render_distance_map(&unreached).lines().nth(2), Some("001 ............"));
+    }
+
+    #[test]
+    fn placement_overlay_stars_the_claimed_footprint() {
+        let (state, offsets, tx, ty) = frame(&["......", ".@....", "......", ".....$"], &[".....", ".#...", ".##.."]);
+        assert_eq!((tx, ty), (1, 1));
+        assert!(state.is_legal_move(1, 1, &offsets));
+        let expected = "    012345\n\
+                        000 ......\n\
+                        001 .@....\n\
+                        002 .**...\n\
+                        003 .....$\n";
+        assert_eq!(state.render_with_placement(1, 1, &offsets), expected);
+    }
+}
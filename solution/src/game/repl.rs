@@ -0,0 +1,116 @@
+use super::game_state::GameState;
+use super::turn::BoardRead;
+use std::io::{self, BufRead, Write};
+
+/// Most analyzed moves listed per piece
+const REPL_MAX_ROWS: usize = 10;
+
+/// Interactive position explorer
+///
+/// Accepts the engine's own input format, typed or pasted: a `$$$ exec pN`
+/// line, an `Anfield` block and then `Piece` blocks. After each piece it
+/// prints the best-ranked moves with their score breakdown and the move the
+/// bot would play. `quit` (or end of input) leaves.
+pub fn run_repl<R: BufRead, W: Write>(input: R, out: &mut W) -> io::Result<()> {
+    let mut state = GameState::new();
+    let mut lines = input.lines();
+    writeln!(out, "filler repl: enter `$$$ exec p1|p2`, an `Anfield` block, then a `Piece` block (`quit` exits)")?;
+
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        let line = line.trim();
+
+        if line == "quit" || line == "exit" {
+            break;
+        } else if line.starts_with("$$$ exec p") {
+            state.parse_player(line);
+            writeln!(out, "playing as {:?}", state.player)?;
+        } else if line.starts_with("Anfield ") {
+            let read = match state.parse_board_dimensions(line) {
+                Ok(()) => state.read_board(&mut lines),
+                Err(e) => {
+                    writeln!(out, "error: {}", e)?;
+                    continue;
+                }
+            };
+            if let BoardRead::Failed = read {
+                writeln!(out, "error: could not read the board")?;
+                continue;
+            }
+            state.last_opponent_centroid = state.opponent_centroid();
+            write!(out, "{}", state.render_board())?;
+            if let BoardRead::Truncated(header) = read {
+                if state.read_piece(&header, &mut lines) {
+                    print_analysis(&mut state, out)?;
+                } else {
+                    writeln!(out, "error: could not read the piece")?;
+                }
+            }
+        } else if line.starts_with("Piece ") {
+            if state.board_height == 0 {
+                writeln!(out, "error: enter a board first")?;
+            } else if state.read_piece(line, &mut lines) {
+                print_analysis(&mut state, out)?;
+            } else {
+                writeln!(out, "error: could not read the piece")?;
+            }
+        } else if !line.is_empty() {
+            writeln!(out, "unrecognized input: {}", line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Print the top moves for the current piece with their score components
+fn print_analysis<W: Write>(state: &mut GameState, out: &mut W) -> io::Result<()> {
+    let (piece_offsets, trim_off_x, trim_off_y) = state.trim_piece();
+    let analyses = state.move_analysis(&piece_offsets, trim_off_x, trim_off_y);
+
+    writeln!(out, "{} legal moves", analyses.len())?;
+    if !analyses.is_empty() {
+        writeln!(out, "{:>4} {:>4} {:>8} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6}",
+                 "x", "y", "score", "new", "lib", "adj", "heat", "conn", "other")?;
+    }
+    for a in analyses.iter().take(REPL_MAX_ROWS) {
+        writeln!(out, "{:>4} {:>4} {:>8} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6}",
+                 a.x - trim_off_x, a.y - trim_off_y, a.score,
+                 a.new_cells, a.liberties, a.adj_op, a.heat_sum, a.connectivity, a.other)?;
+    }
+
+    match state.best_move(&piece_offsets, trim_off_x, trim_off_y) {
+        Some((x, y)) => writeln!(out, "move: {} {}", x, y),
+        None => writeln!(out, "move: none"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::{frame, frame_text};
+
+    #[test]
+    fn scripted_session_prints_the_analysis_and_move() {
+        let rows = ["......", ".@....", "......", ".....$"];
+        let (state, offsets, tx, ty) = frame(&rows, &["##"]);
+        let (x, y) = state.best_move(&offsets, tx, ty).unwrap();
+
+        // A full frame, a second piece for the same board, junk, then quit
+        let script = format!("{}Piece 1 2:\n#\n#\nbogus\nquit\nPiece 1 1:\n#\n", frame_text(1, &rows, &["##"]));
+        let mut out = Vec::new();
+        run_repl(script.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("playing as One"), "{}", out);
+        assert!(out.contains("2 legal moves"), "{}", out);
+        assert!(out.contains(&format!("move: {} {}\n", x, y)), "{}", out);
+        assert_eq!(out.matches("move: ").count(), 2, "{}", out);
+        assert!(out.contains("unrecognized input: bogus"), "{}", out);
+        // Nothing after `quit` is read
+        assert!(out.trim_end().ends_with('>'), "{}", out);
+    }
+}
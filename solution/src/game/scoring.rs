@@ -1,5 +1,50 @@
-use crate::types::{Player, Cell, PieceOffset};
-use super::game_state::GameState;
+use crate::types::{Player, Cell, PieceOffset, MoveAnalysis};
+use super::game_state::{to_board_index, GameState};
+use super::board::Grid;
+use super::strategy::{permille, PERMILLE};
+
+/// Cap on the opponent path cost credited per claimed cell (unreachable counts as the cap)
+const MAX_PATH_COST: i32 = 10;
+/// Cap on the distance to the contested border charged to a placement
+const MAX_FRONTIER_DISTANCE: i32 = 20;
+/// Bonus per key square claimed (see `set_key_squares`)
+const KEY_SQUARE_BONUS: i32 = 100_000;
+/// Bonus for claiming a central cell against a mirroring opponent
+const ANTI_MIRROR_CENTER_BONUS: i32 = 50_000;
+/// Penalty per half-cell between a placement and the center against a mirroring opponent
+const ANTI_MIRROR_PULL: i32 = 200;
+/// Bounds on the momentum scaling of the blocking term
+const MOMENTUM_SCALE_RANGE: (f32, f32) = (0.5, 2.0);
+/// Penalty for reaching past `max_reach_radius`; outweighs every other term,
+/// so a far move only wins when no legal move stays within the radius
+const REACH_PENALTY: i64 = 1_000_000;
+
+/// Per-turn inputs shared by every candidate's score
+///
+/// Built once per turn; read-only afterwards, so threads can share it.
+#[derive(Debug, Clone)]
+pub struct TurnContext {
+    /// Distances to the opponent, row-major, from `calculate_distance_map_flat`
+    pub distance_map: Vec<i32>,
+    /// The piece's trimmed offsets
+    pub piece_offsets: Vec<PieceOffset>,
+    /// Cells the opponent can still reach, from `opponent_reachability`
+    pub opponent_reach: Vec<Vec<bool>>,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Make scoring panic on this thread (test hook for the turn watchdog)
+    pub(crate) static PANIC_IN_SCORING: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+// Scoring from several threads relies on the state and its helpers being shareable
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<GameState>();
+    assert_send_sync::<TurnContext>();
+    assert_send_sync::<MoveAnalysis>();
+};
 
 impl GameState {
     /// Count empty neighbors of a cell
@@ -16,7 +61,7 @@ impl GameState {
                 let nx = nx as usize;
                 let ny = ny as usize;
                 
-                if self.board[ny][nx] == Cell::Empty {
+                if self.board.at(nx, ny) == Cell::Empty {
                     count += 1;
                 }
             }
@@ -36,10 +81,9 @@ impl GameState {
             let nx = x as i32 + dx;
             let ny = y as i32 + dy;
             
-            if nx >= 0 && nx < self.board_width as i32 && ny >= 0 && ny < self.board_height as i32 {
-                if self.board[ny as usize][nx as usize] == my_cell {
-                    count += 1;
-                }
+            if nx >= 0 && nx < self.board_width as i32 && ny >= 0 && ny < self.board_height as i32
+                && self.board.at(nx as usize, ny as usize) == my_cell {
+                count += 1;
             }
         }
         
@@ -48,26 +92,16 @@ impl GameState {
     
     /// Count total empty cells on the board (for endgame detection)
     pub fn count_total_empty_cells(&self) -> i32 {
-        let mut count = 0;
-        for row in &self.board {
-            for cell in row {
-                if *cell == Cell::Empty {
-                    count += 1;
-                }
-            }
-        }
-        count
+        self.empty_cells.len() as i32
     }
     
     /// Count our total territory size
     pub fn count_my_territory(&self) -> i32 {
         let mut count = 0;
         let my_cell = if self.player == Player::One { Cell::Player1 } else { Cell::Player2 };
-        for row in &self.board {
-            for cell in row {
-                if *cell == my_cell {
-                    count += 1;
-                }
+        for (_, _, cell) in self.board.cells() {
+            if cell == my_cell {
+                count += 1;
             }
         }
         count
@@ -77,18 +111,88 @@ impl GameState {
     pub fn count_opponent_territory(&self) -> i32 {
         let mut count = 0;
         let opponent_cell = if self.player == Player::One { Cell::Player2 } else { Cell::Player1 };
-        for row in &self.board {
-            for cell in row {
-                if *cell == opponent_cell {
-                    count += 1;
-                }
+        for (_, _, cell) in self.board.cells() {
+            if cell == opponent_cell {
+                count += 1;
             }
         }
         count
     }
 
-    pub fn score_move(&self, x: i32, y: i32, dist: &[Vec<i32>], piece_offsets: &[PieceOffset]) -> i32 {
-        let my  = if self.player == Player::One { Cell::Player1 } else { Cell::Player2 };
+    /// Replace the aggression schedule with the given (progress, multiplier) control points
+    pub fn set_aggression_curve(&mut self, mut points: Vec<(f32, f32)>) {
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        self.aggression_curve = points;
+    }
+
+    /// Blocking-weight multiplier at the given game progress, linearly interpolated
+    pub fn aggression_multiplier(&self, progress: f32) -> f32 {
+        let curve = &self.aggression_curve;
+        match (curve.first(), curve.last()) {
+            (Some(&(x0, y0)), _) if progress <= x0 => y0,
+            (_, Some(&(xn, yn))) if progress >= xn => yn,
+            (None, _) | (_, None) => 1.0,
+            _ => {
+                for pair in curve.windows(2) {
+                    let (xa, ya) = pair[0];
+                    let (xb, yb) = pair[1];
+                    if progress >= xa && progress <= xb {
+                        if xb - xa <= f32::EPSILON {
+                            return yb;
+                        }
+                        return ya + (yb - ya) * (progress - xa) / (xb - xa);
+                    }
+                }
+                1.0
+            }
+        }
+    }
+
+    pub fn score_move(&self, x: i32, y: i32, ctx: &TurnContext) -> i32 {
+        self.analyze_move(x, y, ctx).map_or(i32::MIN/4, |a| a.score)
+    }
+
+    /// Scoring inputs for the current board and piece
+    pub fn turn_context(&self, piece_offsets: &[PieceOffset]) -> TurnContext {
+        let distance_map = if self.is_small_board() { self.calculate_distance_map_small() } else { self.calculate_distance_map_flat() };
+        TurnContext {
+            distance_map,
+            piece_offsets: piece_offsets.to_vec(),
+            opponent_reach: self.opponent_reachability(),
+        }
+    }
+
+    /// Score each (trimmed) candidate anchor, in order
+    ///
+    /// Pure `&self`: threads may score disjoint slices of one candidate list
+    /// against a shared state and context.
+    pub fn score_all(&self, candidates: &[(i32, i32)], ctx: &TurnContext) -> Vec<i32> {
+        candidates
+            .iter()
+            .map(|&(x, y)| self.score_move(x, y, ctx))
+            .collect()
+    }
+
+    /// Move score per filled piece cell, comparable across turns with different pieces
+    ///
+    /// For analytics only; within a turn the piece is fixed and raw scores rank the same.
+    pub fn normalized_move_score(&self, move_score: i32, piece_cell_count: usize) -> f32 {
+        if piece_cell_count == 0 {
+            return 0.0;
+        }
+        move_score as f32 / piece_cell_count as f32
+    }
+
+    /// Score a placement and keep each weighted term separately
+    ///
+    /// `None` for placements `score_move` rejects (off the board or claiming
+    /// nothing). The components add up to the score unless it saturated.
+    pub fn analyze_move(&self, x: i32, y: i32, ctx: &TurnContext) -> Option<MoveAnalysis> {
+        #[cfg(test)]
+        if PANIC_IN_SCORING.with(|panics| panics.get()) {
+            panic!("injected scoring panic");
+        }
+        let (dist, piece_offsets) = (&ctx.distance_map, &ctx.piece_offsets[..]);
         let op  = if self.player == Player::One { Cell::Player2 } else { Cell::Player1 };
     
         // game phase
@@ -96,64 +200,260 @@ impl GameState {
         let op_t = self.count_opponent_territory();
         let occ  = my_t + op_t;
         let total = (self.board_width * self.board_height) as i32;
-        let p = occ as f32 / total as f32;
+        let p = permille(occ, total); // fixed point, so phase decisions match on every platform
+        let (early_end, late_start) = (self.early_phase_end_permille(), self.late_phase_start_permille());
+        // with no opponent cells (eliminated or not yet seeded) the distance map is all -1
+        // and every opponent term is inert, so liberties and connectivity drive expansion
+        let opponent_present = op_t > 0;
     
         // features
         let mut new_cells = 0;   // empty cells we'll claim
         let mut liberties = 0;   // empty-neighbor count around claimed cells
         let mut heat_sum  = 0;   // sum of distance to opponent (smaller is more pressure)
         let mut adj_op    = 0;   // adjacency to opponent (blocking)
+        let mut path_sum  = 0;   // opponent A* steps to the claimed cells (capped)
+        let mut zone_sum  = 0;   // zone overlay value of the claimed cells
+        let mut key_squares = 0; // user-marked objectives claimed
+        let mut overlap_lib = 0; // empty neighbors of the own cell we overlap (frontier vs interior)
+        let mut overlap_own = 0; // own neighbors of that cell (how deep inside our territory it sits)
+        let my = self.my_cell();
     
         for off in piece_offsets {
-            let bx = (x + off.dx) as usize;
-            let by = (y + off.dy) as usize;
+            // an off-board anchor (from an unfiltered caller) is never worth playing
+            let (bx, by) = to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height)?;
+            let cell = self.board.at(bx, by);
     
-            if self.board[by][bx] == Cell::Empty {
+            if cell == Cell::Empty {
                 new_cells += 1;
     
                 liberties += self.count_empty_neighbors(bx, by);
     
-                let d = dist[by][bx];
+                let d = dist[by * self.board_width + bx];
                 if d > 0 { heat_sum += d; }
     
                 for (dx,dy) in [(1,0),(-1,0),(0,1),(0,-1)] {
                     let nx = bx as i32 + dx;
                     let ny = by as i32 + dy;
-                    if nx >= 0 && ny >= 0 && nx < self.board_width as i32 && ny < self.board_height as i32 {
-                        if self.board[ny as usize][nx as usize] == op { adj_op += 1; }
-                    }
+                    if nx >= 0 && ny >= 0 && nx < self.board_width as i32 && ny < self.board_height as i32
+                        && self.board.at(nx as usize, ny as usize) == op { adj_op += 1; }
+                }
+    
+                if let Some(zones) = &self.zone_values {
+                    zone_sum += zones.get(by).and_then(|row| row.get(bx)).copied().unwrap_or(0);
+                }
+    
+                if self.key_squares.contains(&(bx, by)) {
+                    key_squares += 1;
+                }
+    
+                if self.path_cost_weight != 0 && opponent_present {
+                    path_sum += self.opponent_path_cost((bx, by)).unwrap_or(MAX_PATH_COST).min(MAX_PATH_COST);
                 }
+            } else if cell == my {
+                overlap_lib = self.count_empty_neighbors(bx, by);
+                overlap_own = self.count_my_neighbors(bx, by);
             }
         }
-        if new_cells == 0 { return i32::MIN/4; }
+        if new_cells == 0 { return None; }
     
         // weights by phase
-        let (w_new, w_lib, w_adj, w_heat) = if p < 0.35 {
+        let (w_new, w_lib, w_adj, w_heat) = if p < early_end {
             (150, 40, 15, -5)     // early: expansion + options
-        } else if p < 0.70 {
+        } else if p < late_start {
             (120, 20, 35, -15)    // mid: balance with pressure
         } else {
             (200, 10, 50, -25)    // late: grab cells & choke
         };
     
-        let mut s = 0;
-        s += new_cells * w_new;
-        s += liberties * w_lib;
-        s += adj_op * w_adj;
-        s += heat_sum * w_heat; // negative weight prefers smaller sums (closer to foe)
+        // accumulate in i64: large weights times many cells must not wrap and scramble the ranking
+        let wide = |a: i32, b: i32| a as i64 * b as i64;
+        let new_cells_term = wide(new_cells, w_new);
+        let liberties_term = wide(liberties, w_lib);
+        let mut blocking_scale = self.aggression_multiplier(p as f32 / PERMILLE as f32);
+        if self.momentum_blocking {
+            // a surging opponent needs stopping; a stalling one can be ignored for greed
+            blocking_scale *= self.opponent_growth_rate().clamp(MOMENTUM_SCALE_RANGE.0, MOMENTUM_SCALE_RANGE.1);
+        }
+        let mut adj_op_term = (wide(adj_op, w_adj) as f64 * blocking_scale as f64).round() as i64;
+        let heat_sum_term = wide(heat_sum, w_heat); // negative weight prefers smaller sums (closer to foe)
+        let mut s: i64 = 0; // every other term
+        if opponent_present && self.frontier_weight != 0 {
+            // open space taken away from the opponent's frontier
+            s += wide(self.frontier_denial_score(x, y, piece_offsets), self.frontier_weight);
+        }
+        if opponent_present && self.region_capture_weight != 0 {
+            // sealing a corridor hands us everything behind it
+            let captured = self.opponent_region_captured_from(&ctx.opponent_reach, x, y, piece_offsets) as i32;
+            s += wide(captured, self.region_capture_weight);
+        }
+        if opponent_present && self.anchor_region_weight != 0 {
+            // fewer separate anchor spots leaves the opponent fewer, more predictable moves
+            s += wide(self.anchor_region_reduction(x, y, piece_offsets), self.anchor_region_weight);
+        }
+        s += wide(path_sum, self.path_cost_weight);
+        s += zone_sum as i64;
+        s += wide(key_squares, KEY_SQUARE_BONUS);
+        s += wide(self.orientation_alignment(x, y, piece_offsets), self.orientation_weight);
+        if self.unlock_weight != 0 {
+            // open space a corridor opens up for us, beyond the cells claimed now
+            let unlocked = self.newly_reachable_empties(&self.board, &self.simulate_move(x, y, piece_offsets)) as i32;
+            s += wide(unlocked, self.unlock_weight);
+        }
+    
+        // keep enough open cells around us that we are never forced to pass
+        if self.mobility_weight != 0 {
+            let mobility = self.next_turn_mobility_estimate(&self.simulate_move(x, y, piece_offsets)) as i32;
+            if mobility < self.mobility_floor {
+                s -= wide(self.mobility_floor - mobility, self.mobility_weight);
+            }
+        }
+        s += wide(overlap_lib, self.overlap_frontier_weight);
+        // re-covering an interior cell costs nothing; a frontier cell is better kept for anchoring later
+        s += wide(overlap_own, self.interior_overlap_weight);
+        if self.frontier_openness_weight != 0 {
+            // square pockets next to us still take large pieces; corridors do not
+            let openness = self.frontier_openness_score(&self.simulate_move(x, y, piece_offsets));
+            s += wide(openness, self.frontier_openness_weight);
+        }
+        if self.exposed_frontier_weight != 0 && opponent_present {
+            // a long edge facing contested space is a long edge to defend
+            let after = self.simulate_move(x, y, piece_offsets);
+            let (_, exposed) = self.classify_frontier(&after, &self.opponent_reachability_on(&after));
+            s -= wide(exposed as i32, self.exposed_frontier_weight);
+        }
+        if self.hole_weight != 0 {
+            // single empty cells walled in by us are awkward to fill later
+            s -= wide(self.enclosed_holes_created(x, y, piece_offsets), self.hole_weight);
+        }
+    
+        // cells the opponent can never reach can wait; contested ones first, sweep up late
+        if self.safe_cell_weight != 0 && opponent_present {
+            let reached = &ctx.opponent_reach;
+            let safe_cells = piece_offsets
+                .iter()
+                .filter(|off| {
+                    to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height)
+                        .is_some_and(|(bx, by)| self.board.at(bx, by) == Cell::Empty && !reached[by][bx])
+                })
+                .count() as i32;
+            if p >= late_start {
+                s += wide(safe_cells, self.safe_cell_weight);
+            } else {
+                s -= wide(safe_cells, self.safe_cell_weight);
+            }
+        }
+    
+        // a mirrorer cannot copy a move on the center: take it, or head for it
+        if self.anti_mirror_active() {
+            let claimed: Vec<(i32, i32)> = piece_offsets
+                .iter()
+                .map(|off| (x + off.dx(), y + off.dy()))
+                .filter(|&(cx, cy)| self.board_get(cx, cy) == Some(Cell::Empty))
+                .collect();
+            if claimed.iter().any(|&(cx, cy)| self.is_center_cell(cx, cy)) {
+                s += ANTI_MIRROR_CENTER_BONUS as i64;
+            } else if let Some(nearest) = claimed.iter().map(|&(cx, cy)| self.doubled_center_distance(cx, cy)).min() {
+                s -= wide(nearest, ANTI_MIRROR_PULL);
+            }
+        }
+
+        // tit-for-tat: contest the region mirroring the opponent's territory
+        if self.mirror_weight != 0 {
+            if let Some(d) = self.mirror_distance(x, y, piece_offsets) {
+                s -= (d as f64 * self.mirror_weight as f64).round() as i64;
+            }
+        }
+    
+        // keep a solid blob: no thin tendrils out past the reach radius before the endgame
+        if let (Some(radius), Some((cx, cy))) = (self.max_reach_radius, self.my_centroid()) {
+            let farthest = piece_offsets
+                .iter()
+                .map(|off| ((x + off.dx()) as f32 - cx).abs() + ((y + off.dy()) as f32 - cy).abs())
+                .fold(0.0f32, f32::max);
+            if p < late_start && farthest > radius as f32 {
+                s -= REACH_PENALTY;
+            }
+        }
+
+        // early: wall off the corridor the opponent would rush through toward our seed
+        if self.seed_barrier_weight != 0 && opponent_present && (self.tempo_history.len() as i32) < self.seed_barrier_turns {
+            s += wide(self.seed_barrier_value(x, y, piece_offsets), self.seed_barrier_weight);
+        }
+
+        // mid-game: pull expansion toward the border where ownership is actually decided
+        if self.contested_frontier_weight != 0 && opponent_present && p >= early_end && p < late_start {
+            let ownership = self.signed_ownership(self.player);
+            let nearest = piece_offsets
+                .iter()
+                .filter_map(|off| self.distance_to_contested_frontier(x + off.dx(), y + off.dy(), &ownership))
+                .min();
+            if let Some(d) = nearest {
+                s -= wide(d.min(MAX_FRONTIER_DISTANCE), self.contested_frontier_weight);
+            }
+        }
+
+        // frontier length: more surface early (options), less late (consolidation)
+        let early = p < early_end;
+        if self.frontier_length_weight != 0 && (early || p >= late_start) {
+            let delta = self.frontier_length_delta(x, y, piece_offsets);
+            s += wide(if early { delta } else { -delta }, self.frontier_length_weight);
+        }
+        // centrality: early placements drift toward the center, the pull fading out over the phase
+        if self.centrality_weight != 0 && early {
+            let fade = (early_end - p) as f32 / early_end as f32;
+            let distance = (self.claimed_center_distance(x, y, piece_offsets) * fade).round() as i32;
+            s -= wide(distance, self.centrality_weight);
+        }
+        if self.fill_ratio_weight != 0 {
+            let fill_pct = (self.fill_ratio_after(x, y, piece_offsets, new_cells) * 100.0).round() as i32;
+            s += wide(fill_pct, self.fill_ratio_weight);
+        }
     
         // if behind, add aggression
-        if my_t < op_t { s += adj_op * 20; }
+        if my_t < op_t { adj_op_term += wide(adj_op, 20); }
     
-        // small connectivity bias (stay near our mass)
+        // small connectivity bias (stay near our mass); zero or negative lets walls reach outward
         let mut best_conn = i32::MAX;
         for (tx, ty) in self.get_my_territory_positions() {
             let d = (x - tx as i32).abs() + (y - ty as i32).abs();
             if d < best_conn { best_conn = d; }
         }
-        if my_t > 0 { s += (10 - best_conn.min(10)) * 10; }
+        let connectivity_term = if my_t > 0 { wide(10 - best_conn.min(10), self.connectivity_weight) } else { 0 };
     
-        s
+        // saturate, keeping every real score above the invalid-move sentinel
+        let total = new_cells_term + liberties_term + adj_op_term + heat_sum_term + connectivity_term + s;
+        Some(MoveAnalysis {
+            x,
+            y,
+            score: total.clamp(i32::MIN as i64 / 4 + 1, i32::MAX as i64) as i32,
+            new_cells: new_cells_term,
+            liberties: liberties_term,
+            adj_op: adj_op_term,
+            heat_sum: heat_sum_term,
+            connectivity: connectivity_term,
+            other: s,
+        })
+    }
+
+    /// Claimed cells on the seed-to-seed path count 2, cells beside it 1
+    pub fn seed_barrier_value(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        let path = self.seed_to_seed_path();
+        piece_offsets
+            .iter()
+            .map(|off| (x + off.dx(), y + off.dy()))
+            .filter(|&(cx, cy)| self.board_get(cx, cy) == Some(Cell::Empty))
+            .map(|(cx, cy)| {
+                let nearest = path
+                    .iter()
+                    .map(|&(px, py)| (px as i32 - cx).abs() + (py as i32 - cy).abs())
+                    .min();
+                match nearest {
+                    Some(0) => 2,
+                    Some(1) => 1,
+                    _ => 0,
+                }
+            })
+            .sum()
     }
 
     pub fn get_my_territory_positions(&self) -> Vec<(usize, usize)> {
@@ -161,7 +461,7 @@ impl GameState {
         let mut pos = Vec::new();
         for y in 0..self.board_height {
             for x in 0..self.board_width {
-                if self.board[y][x] == my_cell {
+                if self.board.at(x, y) == my_cell {
                     pos.push((x, y));
                 }
             }
@@ -169,3 +469,286 @@ impl GameState {
         pos
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseError;
+    use crate::game::testing::frame;
+
+    /// How much the centrality term takes off a placement at `weight`
+    fn centrality_penalty(state: &mut GameState, x: i32, y: i32, offsets: &[PieceOffset], weight: i32) -> i32 {
+        let ctx = state.turn_context(offsets);
+        state.centrality_weight = 0;
+        let plain = state.score_move(x, y, &ctx);
+        state.centrality_weight = weight;
+        plain - state.score_move(x, y, &ctx)
+    }
+
+    #[test]
+    fn centrality_pulls_early_placements_to_the_center() {
+        let rows = [".........", ".........", "$........", ".........", "@...@....", ".........", ".........", ".........", "........."];
+        let (mut state, offsets, _, _) = frame(&rows, &["##"]);
+        let edge = centrality_penalty(&mut state, 0, 4, &offsets, 10);
+        let center = centrality_penalty(&mut state, 3, 4, &offsets, 10);
+        assert!(edge > center, "edge {} center {}", edge, center);
+        assert_eq!(center, 0);
+
+        // Outside the early phase the term is gone
+        state.early_phase_end = 0.0;
+        assert_eq!(centrality_penalty(&mut state, 0, 4, &offsets, 10), 0);
+    }
+
+    #[test]
+    fn ramp_curve_raises_blocking_as_the_board_fills() {
+        let mut state = GameState::new();
+        state.set_aggression_curve(vec![(1.0, 3.0), (0.0, 1.0)]);
+        let samples: Vec<f32> = (0..=10).map(|i| state.aggression_multiplier(i as f32 / 10.0)).collect();
+        assert!(samples.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", samples);
+        assert_eq!((samples[0], samples[5], samples[10]), (1.0, 2.0, 3.0));
+
+        // The blocking term for the same contact grows with the fill
+        let blocking_scale = |rows: &[&str]| {
+            let (mut state, offsets, _, _) = frame(rows, &["#", "#"]);
+            let ctx = state.turn_context(&offsets);
+            let flat = state.analyze_move(0, 0, &ctx).unwrap().adj_op as f64;
+            state.set_aggression_curve(vec![(0.0, 1.0), (1.0, 3.0)]);
+            state.analyze_move(0, 0, &ctx).unwrap().adj_op as f64 / flat
+        };
+        let sparse = blocking_scale(&["@.....", ".$....", "......", "......", "......", "......"]);
+        let filled = blocking_scale(&["@.....", ".$....", "......", "@@@@@@", "@@@@@@", "@@@@@@"]);
+        assert!(1.0 < sparse && sparse < filled, "sparse {} filled {}", sparse, filled);
+    }
+
+    #[test]
+    fn off_board_anchors_score_as_rejected() {
+        let (state, offsets, _, _) = frame(&["@...", "....", "...$"], &["##"]);
+        let ctx = state.turn_context(&offsets);
+        for &(x, y) in &[(-1, 0), (3, 0), (0, -5), (0, 3), (i32::MIN, i32::MAX)] {
+            assert_eq!(state.score_move(x, y, &ctx), i32::MIN / 4, "{:?}", (x, y));
+            assert!(state.analyze_move(x, y, &ctx).is_none());
+        }
+    }
+
+    #[test]
+    fn frontier_overlap_is_rewarded_over_an_interior_one() {
+        let rows = [".........", ".@@@.....", ".@@@.....", ".@@@.....", "........$"];
+        let (mut state, offsets, _, _) = frame(&rows, &["#..#"]);
+        let ctx = state.turn_context(&offsets);
+        let bonus = |state: &GameState, x: i32| {
+            let with = state.analyze_move(x, 2, &ctx).unwrap().other;
+            let mut plain = state.clone();
+            plain.overlap_frontier_weight = 0;
+            with - plain.analyze_move(x, 2, &ctx).unwrap().other
+        };
+        state.overlap_frontier_weight = 10;
+        // (2, 2) overlaps the middle of our block, (3, 2) its right edge
+        assert_eq!(bonus(&state, 2), 0);
+        assert_eq!(bonus(&state, 3), 10);
+    }
+
+    #[test]
+    fn extreme_weights_saturate_instead_of_wrapping() {
+        let mut rows = vec![".".repeat(60); 60];
+        rows[0] = format!("@{}", ".".repeat(59));
+        rows[59] = format!("{}$", ".".repeat(59));
+        let full = "#".repeat(30);
+        let small = ["##"];
+        let big: Vec<&str> = (0..30).map(|_| full.as_str()).collect();
+
+        for weight in &[i32::MAX, i32::MIN] {
+            let (mut state, offsets, _, _) = frame(&rows, &big);
+            state.fill_ratio_weight = *weight;
+            state.connectivity_weight = *weight;
+            let ctx = state.turn_context(&offsets);
+            let analysis = state.analyze_move(0, 0, &ctx).unwrap();
+            assert!(analysis.score > i32::MIN / 4, "{}", analysis.score);
+            // The wide terms keep their sign rather than wrapping around
+            assert_eq!(analysis.connectivity.signum(), weight.signum() as i64);
+        }
+
+        // A positive overflow pins the score at the top instead of wrapping negative
+        let (mut state, offsets, _, _) = frame(&rows, &small);
+        state.connectivity_weight = i32::MAX;
+        let ctx = state.turn_context(&offsets);
+        assert_eq!(state.score_move(0, 0, &ctx), i32::MAX);
+    }
+
+    /// What a knob adds to the `other` component of a placement
+    fn term(state: &GameState, x: i32, y: i32, offsets: &[PieceOffset], knob: fn(&mut GameState) -> &mut i32, weight: i32) -> i64 {
+        let ctx = state.turn_context(offsets);
+        let (mut on, mut off) = (state.clone(), state.clone());
+        *knob(&mut on) = weight;
+        *knob(&mut off) = 0;
+        on.analyze_move(x, y, &ctx).unwrap().other - off.analyze_move(x, y, &ctx).unwrap().other
+    }
+
+    #[test]
+    fn mobility_penalty_hits_the_move_into_a_dead_end() {
+        let (state, offsets, _, _) = frame(&["$$$....", "$.@....", "$$$....", ".......", "......$"], &["##"]);
+        let dead_end = state.next_turn_mobility_estimate(&state.simulate_move(1, 1, &offsets));
+        let open = state.next_turn_mobility_estimate(&state.simulate_move(2, 1, &offsets));
+        assert_eq!((dead_end, open), (1, 4));
+
+        assert_eq!(term(&state, 1, 1, &offsets, |s| &mut s.mobility_weight, 10), -30);
+        assert_eq!(term(&state, 2, 1, &offsets, |s| &mut s.mobility_weight, 10), 0);
+    }
+
+    #[test]
+    fn hole_penalty_hits_the_move_that_walls_in_a_cell() {
+        let (state, offsets, _, _) = frame(&[".@.....", "@.@....", "..@....", "......$"], &["##"]);
+        // Covering (1, 2) closes (1, 1) off on all four sides
+        assert_eq!(state.enclosed_holes_created(1, 2, &offsets), 1);
+        assert_eq!(state.enclosed_holes_created(2, 2, &offsets), 0);
+
+        assert_eq!(term(&state, 1, 2, &offsets, |s| &mut s.hole_weight, 25), -25);
+        assert_eq!(term(&state, 2, 2, &offsets, |s| &mut s.hole_weight, 25), 0);
+    }
+
+    #[test]
+    fn center_weighted_zones_favour_the_inward_move() {
+        let rows = [".........", ".........", ".........", ".........", "$.@......", ".........", ".........", ".........", "........."];
+        let (mut state, offsets, _, _) = frame(&rows, &["##"]);
+        let ctx = state.turn_context(&offsets);
+        let plain = |s: &GameState| (s.score_move(1, 4, &ctx), s.score_move(2, 4, &ctx));
+        let (outward, inward) = plain(&state);
+
+        // Worth 200 at the center, 50 less per step away
+        let zones = (0..9).map(|y: i32| (0..9).map(|x: i32| 200 - 50 * ((x - 4).abs() + (y - 4).abs())).collect()).collect();
+        state.set_zone_values(zones).unwrap();
+        // The claimed cell is (1, 4) or (3, 4)
+        assert_eq!(plain(&state), (outward + 50, inward + 150));
+
+        assert_eq!(state.set_zone_values(vec![vec![0; 9]; 8]), Err(ParseError::ZoneSizeMismatch { expected: (9, 9), got: (9, 8) }));
+    }
+
+    #[test]
+    fn normalized_scores_are_comparable_across_piece_sizes() {
+        assert_eq!(GameState::new().normalized_move_score(600, 4), GameState::new().normalized_move_score(300, 2));
+        assert_eq!(GameState::new().normalized_move_score(300, 0), 0.0);
+
+        // Best open-board placements of a domino and a 2x2 square, each claiming all but one cell
+        let rows = ["..........", "..........", "..........", "....@.....", "..........", "..........", "..........", ".........$"];
+        let eval = |piece: &[&str]| {
+            let (state, offsets, tx, ty) = frame(&rows, piece);
+            let (_, _, score) = state.best_move_with_eval(&offsets, tx, ty).unwrap();
+            (score as f32, state.normalized_move_score(score, offsets.len()))
+        };
+        let ((small, small_norm), (large, large_norm)) = (eval(&["##"]), eval(&["##", "##"]));
+        assert!(large / small > 2.0, "{} vs {}", large, small);
+        assert!(large_norm / small_norm < 1.3, "{} vs {}", large_norm, small_norm);
+    }
+
+    #[test]
+    fn moves_toward_the_contested_border_score_higher() {
+        // Mid-game; the opponent holds the top-right, so the border runs down from there
+        let rows = ["@@@....$$$", "@@@....$$$", "@@@.......", "@@@.......", "@@@.......", "@@@......."];
+        let (state, offsets, _, _) = frame(&rows, &["##"]);
+        let ownership = state.signed_ownership(state.player);
+        assert_eq!(state.distance_to_contested_frontier(3, 0, &ownership), Some(1));
+        assert_eq!(state.distance_to_contested_frontier(3, 5, &ownership), Some(3));
+
+        assert_eq!(term(&state, 2, 0, &offsets, |s| &mut s.contested_frontier_weight, 10), -10);
+        assert_eq!(term(&state, 2, 5, &offsets, |s| &mut s.contested_frontier_weight, 10), -30);
+    }
+
+    #[test]
+    fn scoring_slices_on_several_threads_matches_one_pass() {
+        let rows = crate::game::testing::random_rows(11, 30, 20, 70);
+        let (state, offsets, tx, ty) = frame(&rows, &["##", ".#"]);
+        let candidates = state.find_legal_moves(&offsets, tx, ty);
+        assert!(candidates.len() > 8);
+        let ctx = state.turn_context(&offsets);
+
+        let sequential = state.score_all(&candidates, &ctx);
+        let parallel: Vec<i32> = std::thread::scope(|scope| {
+            let workers: Vec<_> = candidates
+                .chunks(candidates.len() / 4 + 1)
+                .map(|slice| {
+                    let (state, ctx) = (&state, &ctx);
+                    scope.spawn(move || state.score_all(slice, ctx))
+                })
+                .collect();
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        });
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn claiming_the_seed_to_seed_path_earns_the_barrier_term() {
+        let rows = ["........", "........", ".@....$.", "........", "........"];
+        let (state, offsets, _, _) = frame(&rows, &["##"]);
+        assert_eq!(state.seed_to_seed_path(), vec![(2, 2), (3, 2), (4, 2), (5, 2)]);
+        // (2, 2) sits on the path, (0, 2) two steps off it
+        assert_eq!(state.seed_barrier_value(1, 2, &offsets), 2);
+        assert_eq!(state.seed_barrier_value(0, 2, &offsets), 0);
+
+        assert_eq!(term(&state, 1, 2, &offsets, |s| &mut s.seed_barrier_weight, 10), 20);
+        assert_eq!(term(&state, 0, 2, &offsets, |s| &mut s.seed_barrier_weight, 10), 0);
+    }
+
+    #[test]
+    fn reach_radius_penalizes_the_far_tendril_move() {
+        // Our centroid is (2.8, 1.4); the arm along row 2 reaches out to x = 8
+        let rows = ["@@@.......", "@@@.......", "@@@@@@@@@.", "..........", "..........", ".........$"];
+        let (mut state, offsets, tx, ty) = frame(&rows, &["##"]);
+        let ctx = state.turn_context(&offsets);
+        let other = |s: &GameState, x, y| s.analyze_move(x, y, &ctx).unwrap().other;
+        let (far, near) = (other(&state, 8, 2), other(&state, 3, 1));
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((8, 2)));
+
+        state.max_reach_radius = Some(5);
+        assert_eq!(other(&state, 8, 2), far - REACH_PENALTY);
+        assert_eq!(other(&state, 3, 1), near);
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 1)));
+
+        // With every placement out of reach the penalty cancels out
+        state.max_reach_radius = Some(1);
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((8, 2)));
+    }
+
+    #[test]
+    fn key_square_outranks_the_otherwise_best_move() {
+        let (mut state, offsets, tx, ty) = frame(&["......", "..@...", "......", ".....$"], &["#", "#"]);
+        let ctx = state.turn_context(&offsets);
+        // Down toward them scores better than up
+        let (up, down) = (state.score_move(2, 0, &ctx), state.score_move(2, 1, &ctx));
+        assert!(down > up, "up {} down {}", up, down);
+        // The opening shortcut plays up anyway
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 0)));
+
+        state.set_key_squares(vec![(2, 0)]);
+        assert_eq!(state.score_move(2, 0, &ctx), up + KEY_SQUARE_BONUS);
+        assert_eq!(state.score_move(2, 1, &ctx), down);
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 0)));
+        // Key squares switch the shortcut off, so scoring decides
+        state.set_key_squares(vec![(2, 2)]);
+        assert_eq!(state.best_move(&offsets, tx, ty), Some((2, 1)));
+    }
+
+    #[test]
+    fn overlap_deep_in_our_territory_beats_one_on_the_frontier() {
+        let (state, offsets, _, _) = frame(&["@@.....", "@@..@..", "@@.....", "......$"], &["###"]);
+        // Both claim (2, 1) and (3, 1): one overlaps (1, 1) inside our block, the other the lone (4, 1)
+        assert_eq!(state.count_claimed_cells(1, 1, &offsets), 2);
+        assert_eq!(state.count_claimed_cells(2, 1, &offsets), 2);
+        assert_eq!(term(&state, 1, 1, &offsets, |s| &mut s.interior_overlap_weight, 10), 30);
+        assert_eq!(term(&state, 2, 1, &offsets, |s| &mut s.interior_overlap_weight, 10), 0);
+    }
+
+    #[test]
+    fn zero_connectivity_lets_the_bar_reach_away_from_our_cell() {
+        // Their line runs out on the right, so the bar is worth more stretched left
+        let rows = ["$$$$$$$$...", "...........", "...........", ".....@.....", "..........."];
+        let (mut state, offsets, _, _) = frame(&rows, &["#####"]);
+        let ctx = state.turn_context(&offsets);
+        // Anchored four cells left of our cell, or on it
+        let (far, near) = (state.analyze_move(1, 3, &ctx).unwrap(), state.analyze_move(5, 3, &ctx).unwrap());
+        assert_eq!((far.connectivity, near.connectivity), (60, 100));
+        assert!(far.score < near.score, "far {} near {}", far.score, near.score);
+
+        state.connectivity_weight = 0;
+        assert!(state.score_move(1, 3, &ctx) > state.score_move(5, 3, &ctx));
+        assert_eq!(state.analyze_move(1, 3, &ctx).unwrap().connectivity, 0);
+    }
+}
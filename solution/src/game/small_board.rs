@@ -0,0 +1,140 @@
+use crate::types::{PieceOffset, ScoredMove};
+use super::game_state::GameState;
+use super::board::Grid;
+
+/// Largest board side handled by the small-board fast path
+pub const SMALL_BOARD_MAX: usize = 16;
+
+impl GameState {
+    /// Check whether the board fits the fixed-size fast path
+    pub fn is_small_board(&self) -> bool {
+        self.board_width <= SMALL_BOARD_MAX && self.board_height <= SMALL_BOARD_MAX
+    }
+
+    /// Distance map for small boards, computed on stack arrays
+    ///
+    /// Produces exactly the same buffer as `calculate_distance_map_flat`.
+    pub fn calculate_distance_map_small(&self) -> Vec<i32> {
+        #[cfg(test)]
+        super::game_state::DISTANCE_MAPS.with(|maps| maps.set(maps.get() + 1));
+        let mut dist = [[-1i32; SMALL_BOARD_MAX]; SMALL_BOARD_MAX];
+        let mut queue = [(0u8, 0u8); SMALL_BOARD_MAX * SMALL_BOARD_MAX];
+        let mut head = 0;
+        let mut tail = 0;
+        let op = self.opponent_cell();
+
+        // Seed the queue with opponent cells
+        for (x, y, cell) in self.board.cells() {
+            if cell == op {
+                dist[y][x] = 0;
+                queue[tail] = (x as u8, y as u8);
+                tail += 1;
+            }
+        }
+
+        // BFS in the same direction order as the general path
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while head < tail {
+            let (x, y) = queue[head];
+            head += 1;
+            let current_dist = dist[y as usize][x as usize];
+
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx >= 0 && nx < self.board_width as i32 && ny >= 0 && ny < self.board_height as i32
+                    && dist[ny as usize][nx as usize] == -1 {
+                    dist[ny as usize][nx as usize] = current_dist + 1;
+                    queue[tail] = (nx as u8, ny as u8);
+                    tail += 1;
+                }
+            }
+        }
+
+        let mut flat = Vec::with_capacity(self.board_width * self.board_height);
+        for row in &dist[..self.board_height] {
+            flat.extend_from_slice(&row[..self.board_width]);
+        }
+        flat
+    }
+
+    /// Single-pass legal scan and scoring for small boards
+    ///
+    /// Scores each anchor as soon as it is found legal instead of collecting
+    /// the legal moves first. When `max_candidates` binds, the same capped
+    /// list as the general path is scored instead, and the lookahead depth is
+    /// taken from the same candidate count, so the chosen move (and its score)
+    /// matches `best_move_general`.
+    pub fn best_move_small(&self, piece_offsets: &[PieceOffset], trim_off_x: i32, trim_off_y: i32) -> Option<(i32, i32, i32)> {
+        if self.piece_width > self.board_width || self.piece_height > self.board_height {
+            return None;
+        }
+        let ctx = self.turn_context(piece_offsets);
+
+        let legal_count = self.legal_move_count(piece_offsets, trim_off_x, trim_off_y);
+        let capped = self.max_candidates != 0 && legal_count > self.max_candidates;
+        let depth = self.adaptive_depth(if capped { self.max_candidates } else { legal_count });
+        let mut best: Option<ScoredMove> = None;
+        if capped {
+            for (x, y) in self.cap_candidates(self.find_legal_moves(piece_offsets, trim_off_x, trim_off_y), piece_offsets) {
+                self.consider_move(&mut best, x, y, piece_offsets, &ctx, depth);
+            }
+        } else {
+            let end_x = self.board_width as i32 - self.piece_width as i32 + trim_off_x;
+            let end_y = self.board_height as i32 - self.piece_height as i32 + trim_off_y;
+            for y in trim_off_y..=end_y {
+                for x in trim_off_x..=end_x {
+                    if self.is_legal_move(x, y, piece_offsets) {
+                        self.consider_move(&mut best, x, y, piece_offsets, &ctx, depth);
+                    }
+                }
+            }
+        }
+
+        // Convert TRIMMED anchor → ORIGINAL top-left for the engine
+        best.map(|m| ((m.x - trim_off_x).max(0), (m.y - trim_off_y).max(0), m.score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ScanOrder;
+    use super::super::testing::{frame_as, random_rows};
+
+    #[test]
+    fn small_distance_map_matches_the_general_one() {
+        for seed in 0..30 {
+            let width = 1 + (seed as usize * 7) % SMALL_BOARD_MAX;
+            let height = 1 + (seed as usize * 5) % SMALL_BOARD_MAX;
+            let (state, _, _, _) = frame_as(1 + (seed % 2) as u8, &random_rows(seed, width, height, 90), &["#"]);
+            let small = state.calculate_distance_map_small();
+            assert_eq!(small, state.calculate_distance_map_flat(), "seed {}", seed);
+            assert_eq!(small.chunks(width).map(<[i32]>::to_vec).collect::<Vec<_>>(), state.calculate_distance_map(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn small_path_picks_the_same_move_as_the_general_one() {
+        let pieces: [&[&str]; 3] = [&["##"], &[".#", "##"], &["#..", "###"]];
+        for seed in 0..120u64 {
+            let width = 2 + (seed as usize * 7) % (SMALL_BOARD_MAX - 1);
+            let height = 2 + (seed as usize * 5) % (SMALL_BOARD_MAX - 1);
+            let rows = random_rows(seed, width, height, 60 + seed % 30);
+            let piece = pieces[seed as usize % pieces.len()];
+            let (mut state, offsets, tx, ty) = frame_as(1 + (seed % 2) as u8, &rows, piece);
+            for &(cap, order) in &[(0, ScanOrder::RowMajor), (4, ScanOrder::RowMajor), (4, ScanOrder::ColumnMajor)] {
+                state.max_candidates = cap;
+                state.scan_order = order;
+                assert_eq!(
+                    state.best_move_small(&offsets, tx, ty),
+                    state.best_move_general(&offsets, tx, ty),
+                    "seed {} cap {} {:?}",
+                    seed,
+                    cap,
+                    order
+                );
+            }
+        }
+    }
+}
@@ -1,20 +1,174 @@
-use crate::types::{ScoredMove, Cell, PieceOffset};
-use super::game_state::GameState;
+use crate::types::{ScoredMove, Cell, PieceOffset, TieBreak};
+use super::game_state::{to_board_index, GameState};
+use super::board::Grid;
+use std::cmp::Ordering;
+
+/// Deepest lookahead we ever ask for, however cheap the turn is
+pub const MAX_LOOKAHEAD_DEPTH: u8 = 3;
+
+/// Fixed-point scale for game progress, so phase decisions are integer math
+pub const PERMILLE: i32 = 1000;
+
+/// `part / whole` in permille, rounded down (0 for an empty whole)
+pub fn permille(part: i32, whole: i32) -> i32 {
+    if whole <= 0 {
+        return 0;
+    }
+    (part as i64 * PERMILLE as i64 / whole as i64) as i32
+}
+
+/// A configured fraction (such as a phase threshold) in permille
+pub fn fraction_to_permille(fraction: f32) -> i32 {
+    (fraction * PERMILLE as f32).round() as i32
+}
 
 impl GameState {
+    /// Permille of the board already occupied by either player
+    pub fn game_progress_permille(&self) -> i32 {
+        let occupied = self.count_my_territory() + self.count_opponent_territory();
+        permille(occupied, (self.board_width * self.board_height) as i32)
+    }
+
+    /// Fraction of the board already occupied by either player
+    pub fn game_progress(&self) -> f32 {
+        self.game_progress_permille() as f32 / PERMILLE as f32
+    }
+
+    /// `early_phase_end` in permille
+    pub fn early_phase_end_permille(&self) -> i32 {
+        fraction_to_permille(self.early_phase_end)
+    }
+
+    /// `late_phase_start` in permille
+    pub fn late_phase_start_permille(&self) -> i32 {
+        fraction_to_permille(self.late_phase_start)
+    }
+
+    /// Manhattan distance (in half-cells) from a placement's center to the board center
+    pub fn center_distance(&self, candidate: &ScoredMove) -> i32 {
+        let cx2 = 2 * candidate.x + self.piece_width as i32 - 1;
+        let cy2 = 2 * candidate.y + self.piece_height as i32 - 1;
+        (cx2 - (self.board_width as i32 - 1)).abs() + (cy2 - (self.board_height as i32 - 1)).abs()
+    }
+
+    /// Manhattan distance from the centroid of a placement's cells to the board center
+    pub fn claimed_center_distance(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> f32 {
+        if piece_offsets.is_empty() {
+            return 0.0;
+        }
+        let n = piece_offsets.len() as f32;
+        let cx = piece_offsets.iter().map(|o| (x + o.dx()) as f32).sum::<f32>() / n;
+        let cy = piece_offsets.iter().map(|o| (y + o.dy()) as f32).sum::<f32>() / n;
+        let center_x = (self.board_width as f32 - 1.0) / 2.0;
+        let center_y = (self.board_height as f32 - 1.0) / 2.0;
+        (cx - center_x).abs() + (cy - center_y).abs()
+    }
+
+    /// Lookahead depth that keeps this turn within the node budget
+    ///
+    /// Each extra ply multiplies the work by roughly the legal-move count, so
+    /// the depth is the largest `d` with `legal_count^(d + 1) <= node_budget`
+    /// (see `lookahead_score`). A zero budget turns the lookahead off.
+    pub fn adaptive_depth(&self, legal_count: usize) -> u8 {
+        if self.node_budget == 0 {
+            return 0;
+        }
+        if legal_count <= 1 {
+            return MAX_LOOKAHEAD_DEPTH;
+        }
+        let mut depth = 0;
+        let mut nodes = legal_count.saturating_mul(legal_count);
+        while depth < MAX_LOOKAHEAD_DEPTH && nodes <= self.node_budget {
+            depth += 1;
+            nodes = nodes.saturating_mul(legal_count);
+        }
+        depth
+    }
+
+    /// Order two candidates: score first, then the configured tie-break, then
+    /// `break_tie`, and coordinate order last
+    ///
+    /// `Greater` means `a` is the better move.
+    pub fn compare_moves(&self, a: &ScoredMove, b: &ScoredMove, piece_offsets: &[PieceOffset]) -> Ordering {
+        let score_cmp = a.score.cmp(&b.score);
+        if score_cmp != Ordering::Equal {
+            return score_cmp;
+        }
+
+        // Center bias only matters while we are still expanding
+        if self.tie_break == TieBreak::CenterBias && self.game_progress_permille() < self.early_phase_end_permille() {
+            let center_cmp = self.center_distance(b).cmp(&self.center_distance(a));
+            if center_cmp != Ordering::Equal {
+                return center_cmp;
+            }
+        }
+
+        let secondary = self.break_tie(a, b, piece_offsets);
+        if secondary != Ordering::Equal {
+            return secondary;
+        }
+        a.cmp(b)
+    }
+
+    /// Cheap secondary comparison for equal scores: more open cells within two
+    /// steps of the claimed cells, then more contacts with our own territory
+    ///
+    /// `Greater` means `a` is the better move; `Equal` leaves it to coordinates.
+    pub fn break_tie(&self, a: &ScoredMove, b: &ScoredMove, piece_offsets: &[PieceOffset]) -> Ordering {
+        let key = |m: &ScoredMove| (self.two_step_liberties(m.x, m.y, piece_offsets), self.claimed_contacts(m.x, m.y, piece_offsets));
+        key(a).cmp(&key(b))
+    }
+
+    /// Distinct empty cells within two steps of a placement's claimed cells (the piece excluded)
+    fn two_step_liberties(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> usize {
+        let after = self.simulate_move(x, y, piece_offsets);
+        let mut counted = vec![vec![false; self.board_width]; self.board_height];
+        let mut count = 0;
+        for off in piece_offsets {
+            let (cx, cy) = (x + off.dx(), y + off.dy());
+            if self.board_get(cx, cy) != Some(Cell::Empty) {
+                continue;
+            }
+            for dy in -2i32..=2 {
+                for dx in -2i32..=2 {
+                    let (nx, ny) = (cx + dx, cy + dy);
+                    if dx.abs() + dy.abs() > 2 || nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    if after.at(nx, ny) == Cell::Empty && !counted[ny][nx] {
+                        counted[ny][nx] = true;
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Our cells orthogonally adjacent to a placement's claimed cells
+    fn claimed_contacts(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        piece_offsets
+            .iter()
+            .map(|off| (x + off.dx(), y + off.dy()))
+            .filter(|&(cx, cy)| self.board_get(cx, cy) == Some(Cell::Empty))
+            .map(|(cx, cy)| self.count_my_neighbors(cx as usize, cy as usize))
+            .sum()
+    }
+
     /// STRATEGIC MOVE SELECTION: Advanced move selection when multiple good options exist
     pub fn select_strategic_move(&self, scored_moves: &[ScoredMove], distance_map: &[Vec<i32>], piece_offsets: &[PieceOffset]) -> ScoredMove {
         // Get game state context
         let my_territory = self.count_my_territory();
         let opponent_territory = self.count_opponent_territory();
-        let total_cells = self.board_width * self.board_height;
-        let game_progress = (my_territory + opponent_territory) as f32 / total_cells as f32;
+        let total_cells = (self.board_width * self.board_height) as i32;
+        let progress = permille(my_territory + opponent_territory, total_cells);
         
         // Consider top 5 moves for strategic analysis
         let top_moves = &scored_moves[0..scored_moves.len().min(5)];
         
         // Early game (< 30% filled): Focus on expansion and positioning
-        if game_progress < 0.3 {
+        if progress < 300 {
             // Prefer moves that maximize future expansion potential
             let mut best_expansion_move = &top_moves[0];
             let mut best_expansion_score = 0;
@@ -24,31 +178,29 @@ impl GameState {
                 
                 // Calculate expansion potential for this move
                 for offset in piece_offsets {
-                    let board_x = move_candidate.x + offset.dx;
-                    let board_y = move_candidate.y + offset.dy;
-                    
-                    if board_x >= 0 && board_x < self.board_width as i32 && 
-                       board_y >= 0 && board_y < self.board_height as i32 {
-                        let bx = board_x as usize;
-                        let by = board_y as usize;
-                        
-                        if self.board[by][bx] == Cell::Empty {
+                    let placed = to_board_index(move_candidate.x + offset.dx(), move_candidate.y + offset.dy(), self.board_width, self.board_height);
+                    if let Some((bx, by)) = placed {
+                        if self.board.at(bx, by) == Cell::Empty {
                             expansion_potential += self.count_empty_neighbors(bx, by);
                         }
                     }
                 }
                 
-                if expansion_potential > best_expansion_score {
+                let is_better = expansion_potential > best_expansion_score
+                    || (expansion_potential == best_expansion_score
+                        && self.tie_break == TieBreak::CenterBias
+                        && self.center_distance(move_candidate) < self.center_distance(best_expansion_move));
+                if is_better {
                     best_expansion_score = expansion_potential;
                     best_expansion_move = move_candidate;
                 }
             }
             
-            return best_expansion_move.clone();
+            best_expansion_move.clone()
         }
         
         // Mid game (30-70% filled): Balance between expansion and blocking
-        else if game_progress < 0.7 {
+        else if progress < 700 {
             // If we're behind, prioritize aggressive expansion
             if my_territory < opponent_territory {
                 // Find move that captures the most territory
@@ -59,15 +211,9 @@ impl GameState {
                     let mut territory_captured = 0;
                     
                     for offset in piece_offsets {
-                        let board_x = move_candidate.x + offset.dx;
-                        let board_y = move_candidate.y + offset.dy;
-                        
-                        if board_x >= 0 && board_x < self.board_width as i32 && 
-                           board_y >= 0 && board_y < self.board_height as i32 {
-                            let bx = board_x as usize;
-                            let by = board_y as usize;
-                            
-                            if self.board[by][bx] == Cell::Empty {
+                        let placed = to_board_index(move_candidate.x + offset.dx(), move_candidate.y + offset.dy(), self.board_width, self.board_height);
+                        if let Some((bx, by)) = placed {
+                            if self.board.at(bx, by) == Cell::Empty {
                                 territory_captured += 1;
                             }
                         }
@@ -79,7 +225,7 @@ impl GameState {
                     }
                 }
                 
-                return best_territory_move.clone();
+                best_territory_move.clone()
             }
             // If we're ahead or equal, balance expansion with blocking
             else {
@@ -88,20 +234,13 @@ impl GameState {
                 let mut best_balance_score = 0;
                 
                 for move_candidate in top_moves {
-                    let mut balance_score = 0;
                     let mut territory_captured = 0;
                     let mut blocking_value = 0;
                     
                     for offset in piece_offsets {
-                        let board_x = move_candidate.x + offset.dx;
-                        let board_y = move_candidate.y + offset.dy;
-                        
-                        if board_x >= 0 && board_x < self.board_width as i32 && 
-                           board_y >= 0 && board_y < self.board_height as i32 {
-                            let bx = board_x as usize;
-                            let by = board_y as usize;
-                            
-                            if self.board[by][bx] == Cell::Empty {
+                        let placed = to_board_index(move_candidate.x + offset.dx(), move_candidate.y + offset.dy(), self.board_width, self.board_height);
+                        if let Some((bx, by)) = placed {
+                            if self.board.at(bx, by) == Cell::Empty {
                                 territory_captured += 1;
                                 let opponent_distance = distance_map[by][bx];
                                 if opponent_distance != -1 && opponent_distance <= 4 {
@@ -111,7 +250,7 @@ impl GameState {
                         }
                     }
                     
-                    balance_score = territory_captured * 100 + blocking_value * 50;
+                    let balance_score = territory_captured * 100 + blocking_value * 50;
                     
                     if balance_score > best_balance_score {
                         best_balance_score = balance_score;
@@ -119,7 +258,7 @@ impl GameState {
                     }
                 }
                 
-                return best_balanced_move.clone();
+                best_balanced_move.clone()
             }
         }
         
@@ -133,15 +272,9 @@ impl GameState {
                 let mut endgame_score = 0;
                 
                 for offset in piece_offsets {
-                    let board_x = move_candidate.x + offset.dx;
-                    let board_y = move_candidate.y + offset.dy;
-                    
-                    if board_x >= 0 && board_x < self.board_width as i32 && 
-                       board_y >= 0 && board_y < self.board_height as i32 {
-                        let bx = board_x as usize;
-                        let by = board_y as usize;
-                        
-                        if self.board[by][bx] == Cell::Empty {
+                    let placed = to_board_index(move_candidate.x + offset.dx(), move_candidate.y + offset.dy(), self.board_width, self.board_height);
+                    if let Some((bx, by)) = placed {
+                        if self.board.at(bx, by) == Cell::Empty {
                             endgame_score += 1000; // High value for each cell
                             // Extra bonus for cells that deny opponent future moves
                             let empty_neighbors = self.count_empty_neighbors(bx, by);
@@ -156,7 +289,78 @@ impl GameState {
                 }
             }
             
-            return best_endgame_move.clone();
+            best_endgame_move.clone()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::frame;
+
+    #[test]
+    fn adaptive_depth_shrinks_as_moves_grow() {
+        let mut state = GameState::new();
+        state.node_budget = 10_000;
+        assert_eq!(state.adaptive_depth(10_000), 0);
+        assert_eq!(state.adaptive_depth(50), 1);
+        assert_eq!(state.adaptive_depth(3), MAX_LOOKAHEAD_DEPTH);
+        state.node_budget = 0;
+        assert_eq!(state.adaptive_depth(3), 0);
+    }
+
+    #[test]
+    fn center_bias_prefers_the_central_move_among_equals() {
+        // Our ring leaves the center few liberties, so the secondary tie-break favours the edge
+        let rows = ["@........", ".........", ".........", "...@@@...", "...@.@...", "...@@@...", ".........", ".........", "........$"];
+        let (mut state, offsets, _, _) = frame(&rows, &["#"]);
+        let (central, edge) = (ScoredMove::new(4, 4, 10), ScoredMove::new(0, 4, 10));
+        assert_eq!(state.compare_moves(&central, &edge, &offsets), Ordering::Less);
+
+        state.tie_break = TieBreak::CenterBias;
+        assert_eq!(state.compare_moves(&central, &edge, &offsets), Ordering::Greater);
+        assert_eq!(state.compare_moves(&edge, &central, &offsets), Ordering::Less);
+        // A better score still wins outright
+        assert_eq!(state.compare_moves(&central, &ScoredMove::new(0, 4, 11), &offsets), Ordering::Less);
+    }
+
+    /// 10x10 board with the first `occupied` cells taken (one of them theirs)
+    fn filled(occupied: usize) -> Vec<String> {
+        (0..10)
+            .map(|y| (0..10).map(|x| match y * 10 + x {
+                0 => '$',
+                i if i < occupied => '@',
+                _ => '.',
+            }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn phase_flips_exactly_at_its_threshold() {
+        // New-cell weight per phase: early 150, mid 120, late 200; the chosen move moves with it
+        let cases = [(34, 340, 150, (4, 2)), (35, 350, 120, (5, 2)), (69, 690, 120, (9, 5)), (70, 700, 200, (0, 6))];
+        for &(occupied, permille, new_cells_term, best) in &cases {
+            let (state, offsets, _, _) = frame(&filled(occupied), &["#", "#"]);
+            assert_eq!(state.game_progress_permille(), permille);
+            let last = occupied - 1;
+            let analysis = state.analyze_move((last % 10) as i32, (last / 10) as i32, &state.turn_context(&offsets)).unwrap();
+            assert_eq!(analysis.new_cells, new_cells_term, "{} occupied", occupied);
+            assert_eq!(state.best_move(&offsets, 0, 0), Some(best), "{} occupied", occupied);
+        }
+    }
+
+    #[test]
+    fn equal_scores_go_to_the_move_with_more_room() {
+        // (0, 0) grows into a corner pinned by the opponent; (3, 3) into open space
+        let rows = ["@.$.....", "$.......", "........", "...@....", "........"];
+        let (state, offsets, _, _) = frame(&rows, &["##"]);
+        let (cramped, open) = (ScoredMove::new(0, 0, 50), ScoredMove::new(3, 3, 50));
+        // Coordinates alone would take the top-left move
+        assert_eq!(cramped.cmp(&open), Ordering::Greater);
+
+        assert_eq!(state.break_tie(&open, &cramped, &offsets), Ordering::Greater);
+        assert_eq!(state.compare_moves(&open, &cramped, &offsets), Ordering::Greater);
+        assert_eq!(state.compare_moves(&cramped, &open, &offsets), Ordering::Less);
+    }
+}
@@ -0,0 +1,115 @@
+use super::game_state::GameState;
+
+/// Recent turns compared when judging the opponent's momentum
+const MOMENTUM_WINDOW: usize = 3;
+
+impl GameState {
+    /// Record how many cells we, and the opponent, gained since the previous board
+    ///
+    /// Called once per board read, after `last_opponent_move` is updated; the
+    /// first board only sets the baseline.
+    pub fn record_tempo(&mut self) {
+        let territory = self.count_my_territory() as usize;
+        if let Some(previous) = self.last_my_territory {
+            self.tempo_history.push(territory.saturating_sub(previous));
+            self.opponent_growth_history.push(self.last_opponent_move.len());
+        }
+        self.last_my_territory = Some(territory);
+    }
+
+    /// Average cells claimed per turn over the recorded history (0 before any turn)
+    ///
+    /// A falling tempo means we are getting boxed in.
+    pub fn average_tempo(&self) -> f32 {
+        if self.tempo_history.is_empty() {
+            return 0.0;
+        }
+        self.tempo_history.iter().sum::<usize>() as f32 / self.tempo_history.len() as f32
+    }
+
+    /// Opponent's recent growth relative to ours (1.0 without history)
+    ///
+    /// Cells they gained over the last few turns divided by the cells we
+    /// gained: above 1 they are surging, below 1 they are stalling.
+    pub fn opponent_growth_rate(&self) -> f32 {
+        if self.opponent_growth_history.is_empty() {
+            return 1.0;
+        }
+        let recent = |history: &[usize]| history.iter().rev().take(MOMENTUM_WINDOW).sum::<usize>() as f32;
+        let (theirs, ours) = (recent(&self.opponent_growth_history), recent(&self.tempo_history));
+        theirs / ours.max(1.0)
+    }
+
+    /// Rough number of moves left before the board is full
+    ///
+    /// Empty cells divided by the cells claimed per move, so every move,
+    /// ours and the opponent's, counts as one. A non-positive
+    /// `recent_avg_claim` falls back to `average_tempo`, and with no history
+    /// at all each move is assumed to claim a single cell.
+    pub fn estimated_turns_remaining(&self, recent_avg_claim: f32) -> usize {
+        let empty = self.count_total_empty_cells() as f32;
+        let avg = if recent_avg_claim > 0.0 { recent_avg_claim } else { self.average_tempo() };
+        if avg <= 0.0 {
+            return empty as usize;
+        }
+        (empty / avg).ceil() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::testing::frame_text;
+
+    /// Read a board the way a turn does, updating the tempo history
+    fn observe(state: &mut GameState, rows: &[&str]) {
+        let previous = state.board.clone();
+        state.parse_frame(&frame_text(1, rows, &["#"])).unwrap();
+        state.last_opponent_move = state.diff_opponent_move(&previous);
+        state.record_tempo();
+    }
+
+    #[test]
+    fn tempo_averages_the_cells_claimed_per_turn() {
+        let mut state = GameState::new();
+        observe(&mut state, &["@.....", "......", ".....$"]);
+        assert_eq!(state.average_tempo(), 0.0);
+        observe(&mut state, &["@@....", "......", ".....$"]);
+        observe(&mut state, &["@@@@..", "......", ".....$"]);
+        observe(&mut state, &["@@@@@@", "......", ".....$"]);
+        assert_eq!(state.tempo_history, vec![1, 2, 2]);
+        assert!((state.average_tempo() - 5.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn turns_remaining_shrink_as_the_board_fills() {
+        let mut state = GameState::new();
+        observe(&mut state, &["@.........", "..........", "..........", ".........$"]);
+        let early = state.estimated_turns_remaining(2.0);
+        observe(&mut state, &["@@@@@@@@@@", "@@@@@@@@@@", "$$$$$$$$$$", "$$$.....$$"]);
+        let late = state.estimated_turns_remaining(2.0);
+        assert_eq!((early, late), (19, 3));
+
+        // Without a claim rate, the recorded tempo stands in: 19 cells last turn
+        assert_eq!(state.estimated_turns_remaining(0.0), 1);
+        assert_eq!(GameState::new().estimated_turns_remaining(0.0), 0);
+    }
+
+    #[test]
+    fn surging_opponent_raises_the_blocking_term() {
+        let mut state = GameState::new();
+        observe(&mut state, &["@.....", "......", "....$$"]);
+        observe(&mut state, &["@@....", "......", "..$$$$"]);
+        observe(&mut state, &["@@@...", "....$$", "$$$$$$"]);
+        // They gained 2 then 4 cells while we gained 1 and 1
+        assert_eq!(state.opponent_growth_history, vec![2, 4]);
+        assert_eq!(state.opponent_growth_rate(), 3.0);
+
+        let (offsets, _, _) = state.parse_frame(&frame_text(1, &["@@@...", "....$$", "$$$$$$"], &["#", "#"])).unwrap();
+        let ctx = state.turn_context(&offsets);
+        let flat = state.analyze_move(2, 0, &ctx).unwrap().adj_op;
+        state.momentum_blocking = true;
+        // The weighted contact doubles (the rate, capped); the catch-up bonus of 20 for being behind does not
+        assert_eq!((flat, state.analyze_move(2, 0, &ctx).unwrap().adj_op), (55, 90));
+    }
+}
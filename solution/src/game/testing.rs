@@ -0,0 +1,55 @@
+//! Frame builders shared by the unit tests
+
+use crate::types::PieceOffset;
+use super::game_state::GameState;
+
+/// Frame text for a board and piece, as the engine would send it to `player`
+pub(crate) fn frame_text<R: AsRef<str>>(player: u8, rows: &[R], piece: &[&str]) -> String {
+    let width = rows[0].as_ref().len();
+    let mut text = format!("$$$ exec p{} : [bot]\nAnfield {} {}:\n    {}\n", player, width, rows.len(), "0".repeat(width));
+    for (y, row) in rows.iter().enumerate() {
+        text.push_str(&format!("{:03} {}\n", y, row.as_ref()));
+    }
+    text.push_str(&format!("Piece {} {}:\n", piece[0].len(), piece.len()));
+    for row in piece {
+        text.push_str(row);
+        text.push('\n');
+    }
+    text
+}
+
+/// State for player one with the piece parsed, plus its trimmed offsets and trim offset
+pub(crate) fn frame<R: AsRef<str>>(rows: &[R], piece: &[&str]) -> (GameState, Vec<PieceOffset>, i32, i32) {
+    frame_as(1, rows, piece)
+}
+
+/// `frame` for either player
+pub(crate) fn frame_as<R: AsRef<str>>(player: u8, rows: &[R], piece: &[&str]) -> (GameState, Vec<PieceOffset>, i32, i32) {
+    let mut state = GameState::new();
+    let (offsets, trim_x, trim_y) = state.parse_frame(&frame_text(player, rows, piece)).unwrap();
+    (state, offsets, trim_x, trim_y)
+}
+
+/// State for player one holding just a board
+pub(crate) fn board_state<R: AsRef<str>>(rows: &[R]) -> GameState {
+    frame(rows, &["#"]).0
+}
+
+/// Pseudo-random board rows (LCG), each cell empty with probability `empty_percent`
+pub(crate) fn random_rows(seed: u64, width: usize, height: usize, empty_percent: u64) -> Vec<String> {
+    let mut rng = seed;
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| {
+                    rng = rng.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    match (rng >> 33) % 100 {
+                        r if r < empty_percent => '.',
+                        r if r % 2 == 0 => '@',
+                        _ => '$',
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
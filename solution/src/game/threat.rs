@@ -0,0 +1,76 @@
+use crate::types::Cell;
+use super::game_state::GameState;
+use super::board::Grid;
+
+/// Manhattan radius around a cell searched for opponent frontier cells
+const THREAT_DENSITY_RADIUS: i32 = 2;
+
+impl GameState {
+    /// How likely the opponent is to claim each empty cell soon, in [0, 1]
+    ///
+    /// Averages closeness to their territory (1 / BFS distance) with the share
+    /// of nearby cells on their frontier, so cells next to a broad opponent
+    /// front rank above cells near a lone tip. Cells they cannot reach, and
+    /// cells that are already taken, score 0.
+    pub fn build_threat_map(&self) -> Vec<Vec<f32>> {
+        let mut threat = vec![vec![0.0; self.board_width]; self.board_height];
+        if self.count_opponent_territory() == 0 {
+            return threat;
+        }
+        let distance_map = self.calculate_distance_map();
+        let reached = self.opponent_reachability();
+
+        // Cells in the search diamond, center excluded
+        let r = THREAT_DENSITY_RADIUS;
+        let neighborhood = (2 * r * (r + 1)) as f32;
+
+        for y in 0..self.board_height {
+            for x in 0..self.board_width {
+                if self.board.at(x, y) != Cell::Empty || !reached[y][x] {
+                    continue;
+                }
+                let closeness = 1.0 / distance_map[y][x].max(1) as f32;
+
+                let mut front = 0;
+                for dy in -r..=r {
+                    for dx in -r..=r {
+                        let nx = x as i32 + dx;
+                        let ny = y as i32 + dy;
+                        if (dx, dy) == (0, 0) || dx.abs() + dy.abs() > r
+                            || nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                            continue;
+                        }
+                        if self.is_opponent_frontier_cell(nx as usize, ny as usize) {
+                            front += 1;
+                        }
+                    }
+                }
+
+                threat[y][x] = (closeness + front as f32 / neighborhood) / 2.0;
+            }
+        }
+        threat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::testing::board_state;
+
+    #[test]
+    fn cells_beside_a_clustered_opponent_are_most_threatened() {
+        let state = board_state(&[
+            "@.........",
+            "..........",
+            "..........",
+            ".......$$.",
+            ".......$$.",
+        ]);
+        let threat = state.build_threat_map();
+        assert!(threat[2][8] > threat[1][4]);
+        assert!(threat[1][4] > threat[0][1]);
+        assert!(threat.iter().flatten().all(|&t| (0.0..=1.0).contains(&t)));
+        // Taken cells carry no threat
+        assert_eq!((threat[0][0], threat[3][7]), (0.0, 0.0));
+    }
+}
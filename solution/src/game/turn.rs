@@ -0,0 +1,433 @@
+use super::game_state::GameState;
+use super::board::Grid;
+use std::io::{self, BufRead, Lines, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+/// Outcome of reading the rows after an `Anfield` line
+pub(crate) enum BoardRead {
+    /// Every declared row was read
+    Complete,
+    /// The rows ran out at this `Piece` header; the board keeps the rows read
+    Truncated(String),
+    /// Input ended early or a row could not be parsed
+    Failed,
+}
+
+impl GameState {
+    /// Play a single turn from an input line stream
+    ///
+    /// Reads lines until one move has been written to `out`, handling the player
+    /// line, the `Anfield` board block and the `Piece` block. A `Piece` block that
+    /// arrives without a fresh board is placed on the last board we parsed. A
+    /// player line starts a new game (see `reset_for_new_game`).
+    ///
+    /// Returns `Ok(false)` once the input is exhausted, `Ok(true)` otherwise.
+    pub fn play_turn<R: BufRead, W: Write>(&mut self, lines: &mut Lines<R>, out: &mut W) -> io::Result<bool> {
+        // Process input until a move is emitted or EOF
+        while let Some(line_result) = lines.next() {
+            // Handle potential I/O errors
+            let line = match line_result {
+                Ok(l) => l,
+                Err(e) => {
+                    eprintln!("Error reading line: {}", e);
+                    // Output a safe default move on error
+                    self.write_fallback(out)?;
+                    return Ok(true);
+                }
+            };
+
+            if line.starts_with("$$$ exec p") {
+                // One process may be handed several games in a row
+                self.reset_for_new_game();
+                self.parse_player(&line);
+            }
+            // Parse board dimensions
+            else if line.starts_with("Anfield ") {
+                let previous_board = self.board.clone();
+                if let Err(e) = self.parse_board_dimensions(&line) {
+                    eprintln!("Error parsing board dimensions: {}", e);
+                    self.write_fallback(out)?;
+                    self.discard_board();
+                    return Ok(true);
+                }
+
+                let truncated_at = match self.read_board(lines) {
+                    BoardRead::Complete => None,
+                    BoardRead::Truncated(header) => Some(header),
+                    BoardRead::Failed => {
+                        // Output a safe default move on error
+                        self.write_fallback(out)?;
+                        self.discard_board();
+                        return Ok(true);
+                    }
+                };
+                // Without a player line the Player 1 default could have us playing the wrong side
+                if !self.player_known {
+                    self.infer_player_from_board();
+                }
+                // Track the opponent's position for the mirror heuristic
+                self.last_opponent_centroid = self.opponent_centroid();
+                self.last_opponent_move = self.diff_opponent_move(&previous_board);
+                self.record_tempo();
+                self.record_mirroring();
+
+                // The piece header already arrived in place of a board row
+                if let Some(header) = truncated_at {
+                    self.play_piece(&header, lines, out)?;
+                    return Ok(true);
+                }
+
+                // Look for piece info
+                while let Some(line_result) = lines.next() {
+                    let next_line = match line_result {
+                        Ok(l) => l,
+                        Err(e) => {
+                            eprintln!("Error reading line: {}", e);
+                            break;
+                        }
+                    };
+
+                    if next_line.starts_with("Piece ") {
+                        self.play_piece(&next_line, lines, out)?;
+                        return Ok(true);
+                    }
+                }
+
+                // If we didn't find a piece, output a safe default move
+                self.write_fallback(out)?;
+                return Ok(true);
+            }
+            // Another piece for the board we already hold
+            else if line.starts_with("Piece ") && self.board_height > 0 {
+                self.play_piece(&line, lines, out)?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Forget a board whose turn was already answered with the fallback
+    ///
+    /// The turn's `Piece` block is still unread; with no board held it is
+    /// skipped instead of being answered a second time.
+    fn discard_board(&mut self) {
+        self.board_height = 0;
+        self.board.truncate_rows(0);
+        self.sync_empty_cells();
+    }
+
+    /// Read the column header and board rows following an `Anfield` line
+    ///
+    /// Failures are logged. A `Piece` header arriving before the declared
+    /// height is reached ends the board there: it shrinks to the rows read and
+    /// the header is handed back so the piece can still be played.
+    pub(crate) fn read_board<R: BufRead>(&mut self, lines: &mut Lines<R>) -> BoardRead {
+        // Skip the column header line (e.g., "    01234567890123456789")
+        match lines.next() {
+            Some(Ok(_header_line)) => {
+                // Header line skipped
+            },
+            _ => {
+                eprintln!("Error: Expected header line after board dimensions");
+                return BoardRead::Failed;
+            }
+        }
+
+        // Read board rows
+        let mut board_ok = true;
+        for row_idx in 0..self.board_height {
+            match lines.next() {
+                Some(Ok(board_line)) => {
+                    if board_line.starts_with("Piece ") {
+                        eprintln!("Board declared {} rows but only {} arrived", self.board_height, row_idx);
+                        self.board.truncate_rows(row_idx);
+                        self.board_height = row_idx;
+                        self.sync_empty_cells();
+                        return if board_ok { BoardRead::Truncated(board_line) } else { BoardRead::Failed };
+                    }
+                    if let Err(e) = self.parse_board_row(&board_line, row_idx) {
+                        eprintln!("Error parsing board row {}: {}", row_idx, e);
+                        board_ok = false;
+                    }
+                },
+                _ => {
+                    eprintln!("Unexpected end of input while reading board");
+                    return BoardRead::Failed;
+                }
+            }
+        }
+        if board_ok { BoardRead::Complete } else { BoardRead::Failed }
+    }
+
+    /// Parse a `Piece` block starting at `header` and write our move for it
+    fn play_piece<R: BufRead, W: Write>(&mut self, header: &str, lines: &mut Lines<R>, out: &mut W) -> io::Result<()> {
+        if !self.read_piece(header, lines) {
+            // Output a safe default move on error
+            return self.write_fallback(out);
+        }
+
+        // A panic while deciding must still leave the engine with a move, or it
+        // times us out for the rest of the game
+        let started = Instant::now();
+        let decision = panic::catch_unwind(AssertUnwindSafe(|| {
+            // Trim the piece to its minimal bounding box and get precomputed offsets
+            let (piece_offsets, trim_off_x, trim_off_y) = self.trim_piece();
+
+            // Make a move using the precomputed offsets
+            let choice = self.best_move(&piece_offsets, trim_off_x, trim_off_y);
+            // The opponent's next piece is unknown; ours stands in for it
+            if choice.is_none() && self.is_terminal(&piece_offsets, &piece_offsets) {
+                eprintln!("Neither side can place a piece: the game is decided");
+            }
+            choice
+        }));
+        self.record_decision_time(started.elapsed());
+
+        match decision {
+            Ok(Some((x, y))) => writeln!(out, "{}", self.format_move(x, y))?,
+            Ok(None) => writeln!(out, "0 0")?,
+            Err(payload) => {
+                eprintln!("Panic while choosing a move: {}", panic_message(payload.as_ref()));
+                // The fallback scan may hit the same bug; `0 0` is the last resort
+                let fallback = panic::catch_unwind(AssertUnwindSafe(|| self.any_legal_fallback()))
+                    .unwrap_or_else(|_| "0 0".to_string());
+                writeln!(out, "{}", fallback)?;
+            }
+        }
+        out.flush()
+    }
+
+    /// Track how long a decision took (and log it with `log_timing`)
+    fn record_decision_time(&mut self, elapsed: Duration) {
+        self.last_decision_time = Some(elapsed);
+        self.max_decision_time = self.max_decision_time.max(elapsed);
+        if self.log_timing {
+            eprintln!("Decision took {:?} (slowest {:?})", elapsed, self.max_decision_time);
+        }
+    }
+
+    /// Parse a `Piece` header and the piece rows following it
+    ///
+    /// Returns false (after logging) if the piece could not be read completely.
+    pub(crate) fn read_piece<R: BufRead>(&mut self, header: &str, lines: &mut Lines<R>) -> bool {
+        if let Err(e) = self.parse_piece_dimensions(header) {
+            eprintln!("Error parsing piece dimensions: {}", e);
+            return false;
+        }
+
+        // Read piece rows
+        let mut piece_error = false;
+        for row_idx in 0..self.piece_height {
+            match lines.next() {
+                Some(Ok(piece_line)) => {
+                    if let Err(e) = self.parse_piece_row(&piece_line, row_idx) {
+                        eprintln!("Error parsing piece row {}: {}", row_idx, e);
+                        piece_error = true;
+                    }
+                },
+                _ => {
+                    eprintln!("Unexpected end of input while reading piece");
+                    piece_error = true;
+                    break;
+                }
+            }
+        }
+
+        !piece_error
+    }
+
+    /// Write the safe default move
+    fn write_fallback<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", self.any_legal_fallback())?;
+        out.flush()
+    }
+
+    /// Best guess at a legal move when the turn could not be read properly
+    ///
+    /// `0 0` is usually illegal, so with a board in hand we try the greedy scan
+    /// for whatever piece we parsed, then place a 1×1 piece on one of our own
+    /// cells. Only without a board (or any territory) do we fall back to `0 0`.
+    pub fn any_legal_fallback(&self) -> String {
+        if self.board_height == 0 {
+            return "0 0".to_string();
+        }
+
+        if self.piece_width > 0 && self.piece_height > 0 && self.piece.len() == self.piece_height {
+            let mut state = self.clone();
+            let (piece_offsets, trim_off_x, trim_off_y) = state.trim_piece();
+            if !piece_offsets.is_empty() {
+                if let Some((x, y)) = state.fast_greedy_move(&piece_offsets, trim_off_x, trim_off_y) {
+                    return self.format_move(x, y);
+                }
+            }
+        }
+
+        match self.get_my_territory_positions().first() {
+            Some(&(x, y)) => self.format_move(x as i32, y as i32),
+            None => "0 0".to_string(),
+        }
+    }
+}
+
+/// Text of a panic payload (`panic!` with a literal or a formatted message)
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "unknown panic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Cell;
+    use std::io::Cursor;
+
+    /// Run `play_turn` to the end of `input`, returning every line written
+    fn play_all(state: &mut GameState, input: &str) -> Vec<String> {
+        let mut lines = Cursor::new(input.to_string()).lines();
+        let mut out = Vec::new();
+        while state.play_turn(&mut lines, &mut out).unwrap() {}
+        String::from_utf8(out).unwrap().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn two_turns_write_two_moves() {
+        let input = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\nPiece 2 1:\n##\n\
+                     Anfield 5 3:\n    01234\n000 @@...\n001 .....\n002 ...$$\nPiece 1 2:\n#\n#\n";
+        let mut state = GameState::new();
+        let moves = play_all(&mut state, input);
+        assert_eq!(moves.len(), 2);
+        // The second piece is placed on the second board
+        assert_eq!(state.board.at(1, 0), Cell::Player1);
+        assert_eq!(moves[1], "1 0");
+    }
+
+    #[test]
+    fn follow_up_piece_reuses_the_board() {
+        let input = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\nPiece 2 1:\n##\n\
+                     Piece 1 2:\n#\n#\n";
+        let mut state = GameState::new();
+        assert_eq!(play_all(&mut state, input).len(), 2);
+    }
+
+    #[test]
+    fn corrupt_board_answers_once() {
+        let input = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 3:\n    01234\n000 @..X.\n001 .....\n002 ....$\nPiece 2 1:\n##\n";
+        let mut state = GameState::new();
+        assert_eq!(play_all(&mut state, input).len(), 1);
+    }
+
+    #[test]
+    fn bad_board_header_answers_once() {
+        let input = "$$$ exec p1 : [bot]\nAnfield five 3:\n    01234\n000 @....\nPiece 2 1:\n##\n";
+        let mut state = GameState::new();
+        assert_eq!(play_all(&mut state, input).len(), 1);
+    }
+
+    #[test]
+    fn unreadable_piece_gets_a_legal_fallback_instead_of_the_origin() {
+        // (0, 0) is theirs, so `0 0` would be illegal for any piece
+        let input = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 3:\n    01234\n000 $....\n001 .....\n002 ...@.\nPiece 2 1:\n#?\n";
+        let mut state = GameState::new();
+        assert_eq!(play_all(&mut state, input), vec!["3 2"]);
+
+        // Without a board there is nothing better than the origin
+        assert_eq!(GameState::new().any_legal_fallback(), "0 0");
+    }
+
+    #[test]
+    fn panic_while_scoring_still_writes_a_move() {
+        use crate::game::scoring::PANIC_IN_SCORING;
+        let input = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 3:\n    01234\n000 $....\n001 .....\n002 ..@..\nPiece 2 1:\n##\n";
+        let mut state = GameState::new();
+        PANIC_IN_SCORING.with(|panics| panics.set(true));
+        let moves = play_all(&mut state, input);
+        PANIC_IN_SCORING.with(|panics| panics.set(false));
+        // The greedy fallback's first placement, not `0 0`
+        assert_eq!(moves, vec!["1 2"]);
+
+        // The bot carries on with the next turn
+        let mut lines = Cursor::new("Piece 2 1:\n##\n".to_string()).lines();
+        let mut out = Vec::new();
+        assert!(state.play_turn(&mut lines, &mut out).unwrap());
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn missing_player_line_is_inferred_from_a_player_two_board() {
+        // Player 1 has already moved, so we must be `$`
+        let input = "Anfield 5 3:\n    01234\n000 @@...\n001 .....\n002 ....$\nPiece 2 1:\n##\n";
+        let mut state = GameState::new();
+        assert_eq!(play_all(&mut state, input), vec!["3 2"]);
+        assert_eq!(state.player, crate::types::Player::Two);
+
+        // A symmetric board keeps the player 1 default
+        let input = "Anfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\nPiece 2 1:\n##\n";
+        let mut state = GameState::new();
+        assert_eq!(play_all(&mut state, input), vec!["0 0"]);
+        assert_eq!(state.player, crate::types::Player::One);
+    }
+
+    #[test]
+    fn board_short_of_its_declared_rows_still_gets_the_piece() {
+        // Five rows declared, three sent: the `Piece` line ends the board
+        let input = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 5:\n    01234\n000 .....\n001 .@...\n002 ....$\nPiece 1 2:\n#\n#\n";
+        let mut state = GameState::new();
+        assert_eq!(play_all(&mut state, input), vec!["1 0"]);
+        assert_eq!((state.piece_width, state.piece_height), (1, 2));
+        // The board shrinks to the rows that arrived
+        assert_eq!(state.board_height, 3);
+        assert_eq!(state.board.at(4, 2), Cell::Player2);
+    }
+
+    #[test]
+    fn decision_time_is_recorded_each_turn() {
+        let mut state = GameState::new();
+        assert_eq!(state.last_decision_time, None);
+        let input = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\nPiece 2 1:\n##\n";
+        let mut lines = Cursor::new(input.to_string()).lines();
+        state.play_turn(&mut lines, &mut Vec::new()).unwrap();
+        let first = state.last_decision_time.expect("timed after a decision");
+        assert!(state.max_decision_time >= first);
+
+        // The running max never drops below any single turn
+        let mut lines = Cursor::new("Piece 1 2:\n#\n#\n".to_string()).lines();
+        state.play_turn(&mut lines, &mut Vec::new()).unwrap();
+        let second = state.last_decision_time.unwrap();
+        assert_eq!(state.max_decision_time, first.max(second));
+    }
+
+    #[test]
+    fn second_game_starts_from_a_clean_state() {
+        let first = "$$$ exec p1 : [bot]\n\
+                     Anfield 5 3:\n    01234\n000 @....\n001 .....\n002 ....$\nPiece 2 1:\n##\n\
+                     Anfield 5 3:\n    01234\n000 @@...\n001 .....\n002 ...$$\nPiece 1 2:\n#\n#\n";
+        let second = "$$$ exec p2 : [bot]\n\
+                      Anfield 6 4:\n    012345\n000 ......\n001 .@@...\n002 ......\n003 .....$\nPiece 1 1:\n#\n";
+        let mut state = GameState::new();
+        state.mobility_weight = 7;
+        assert_eq!(play_all(&mut state, first).len(), 2);
+        assert_eq!(state.tempo_history, vec![1]);
+
+        assert_eq!(play_all(&mut state, second), vec!["5 3"]);
+        assert_eq!(state.player, crate::types::Player::Two);
+        assert_eq!((state.board_width, state.board_height), (6, 4));
+        // Nothing carried over from the first game's boards
+        assert!(state.tempo_history.is_empty());
+        assert_eq!(state.last_opponent_move, vec![(1, 1), (2, 1)]);
+        assert_eq!(state.mobility_weight, 7);
+    }
+}
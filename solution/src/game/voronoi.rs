@@ -0,0 +1,225 @@
+use crate::types::{Cell, PieceOffset, Player};
+use super::game_state::{to_board_index, GameState};
+use super::board::{Board, Grid};
+use std::collections::VecDeque;
+
+/// Influence of a claimed cell; an empty cell `d` steps from the nearer side gets this over `1 + d`
+const INFLUENCE_SCALE: i32 = 100;
+
+impl GameState {
+    /// Which player would get to each cell first if both expanded at the same speed
+    ///
+    /// Compares BFS distances through empty cells from both territories (a
+    /// Voronoi split of the open space); claimed cells keep their owner. Cells both sides reach at the same distance go to
+    /// `tie_goes_to` (we move next, so normally ourselves). `None` marks empty
+    /// cells neither side can reach.
+    pub fn ownership_map(&self, tie_goes_to: Player) -> Vec<Vec<Option<Player>>> {
+        let my_dist = self.empty_distances_from(self.my_cell());
+        let op_dist = self.empty_distances_from(self.opponent_cell());
+        let opponent = match self.player {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        };
+
+        let mut owners = vec![vec![None; self.board_width]; self.board_height];
+        for (y, row) in owners.iter_mut().enumerate() {
+            for (x, owner) in row.iter_mut().enumerate() {
+                let (mine, theirs) = (my_dist[y][x], op_dist[y][x]);
+                *owner = match self.board.at(x, y) {
+                    Cell::Player1 => Some(Player::One),
+                    Cell::Player2 => Some(Player::Two),
+                    Cell::Empty if mine == i32::MAX && theirs == i32::MAX => None,
+                    Cell::Empty if mine < theirs => Some(self.player),
+                    Cell::Empty if theirs < mine => Some(opponent),
+                    Cell::Empty => Some(tie_goes_to),
+                };
+            }
+        }
+        owners
+    }
+
+    /// Ownership map as signs: `1` ours, `-1` theirs, `0` unreachable
+    pub fn signed_ownership(&self, tie_goes_to: Player) -> Vec<Vec<i8>> {
+        self.ownership_map(tie_goes_to)
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|owner| match owner {
+                        Some(p) if *p == self.player => 1,
+                        Some(_) => -1,
+                        None => 0,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Manhattan distance from a cell to the nearest contested frontier cell
+    ///
+    /// A contested frontier cell is an empty cell whose ownership sign is the
+    /// opposite of one of its neighbours'. `None` if there is no such border.
+    pub fn distance_to_contested_frontier(&self, x: i32, y: i32, ownership: &[Vec<i8>]) -> Option<i32> {
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        let mut best: Option<i32> = None;
+        for (cy, row) in ownership.iter().enumerate() {
+            for (cx, &sign) in row.iter().enumerate() {
+                if sign == 0 || self.board.at(cx, cy) != Cell::Empty {
+                    continue;
+                }
+                let flips = directions.iter().any(|(dx, dy)| {
+                    let (nx, ny) = (cx as i32 + dx, cy as i32 + dy);
+                    nx >= 0 && ny >= 0
+                        && ownership.get(ny as usize).and_then(|r| r.get(nx as usize)) == Some(&-sign)
+                });
+                if flips {
+                    let d = (cx as i32 - x).abs() + (cy as i32 - y).abs();
+                    if best.is_none_or(|b| d < b) {
+                        best = Some(d);
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Empty cells each side would win in the ownership map, as (ours, theirs)
+    pub fn contested_counts(&self, tie_goes_to: Player) -> (usize, usize) {
+        let owners = self.ownership_map(tie_goes_to);
+        let (mut ours, mut theirs) = (0, 0);
+        for (y, row) in owners.iter().enumerate() {
+            for (x, owner) in row.iter().enumerate() {
+                if self.board.at(x, y) != Cell::Empty {
+                    continue;
+                }
+                match owner {
+                    Some(p) if *p == self.player => ours += 1,
+                    Some(_) => theirs += 1,
+                    None => {}
+                }
+            }
+        }
+        (ours, theirs)
+    }
+
+    /// Change in our sphere of influence if the piece were placed at this anchor
+    ///
+    /// Every cell carries an influence: a claimed cell `±INFLUENCE_SCALE`
+    /// (plus ours, minus theirs), an empty cell that one side reaches first the
+    /// same value divided by `1 + distance`, and a tied or unreachable one
+    /// nothing. Returns the total after the move minus the total before, so
+    /// positive means the move grows our influence more than it grows theirs.
+    pub fn move_influence(&self, x: i32, y: i32, piece_offsets: &[PieceOffset]) -> i32 {
+        let (my, op) = (self.my_cell(), self.opponent_cell());
+        let after = self.simulate_move(x, y, piece_offsets);
+        let my_before = self.empty_distances_on(&self.board, my);
+        let op_before = self.empty_distances_on(&self.board, op);
+        let op_after = self.empty_distances_on(&after, op);
+
+        // Placing only adds sources for us, so our distances can just be relaxed from the new cells
+        let mut my_after = my_before.clone();
+        let mut queue = VecDeque::new();
+        for off in piece_offsets {
+            if let Some((bx, by)) = to_board_index(x + off.dx(), y + off.dy(), self.board_width, self.board_height) {
+                my_after[by][bx] = 0;
+                queue.push_back((bx, by));
+            }
+        }
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some((cx, cy)) = queue.pop_front() {
+            for (dx, dy) in directions.iter() {
+                let nx = cx as i32 + dx;
+                let ny = cy as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if after.at(nx, ny) == Cell::Empty && my_after[cy][cx] + 1 < my_after[ny][nx] {
+                    my_after[ny][nx] = my_after[cy][cx] + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        let influence = |cell: Cell, mine: i32, theirs: i32| match cell {
+            c if c == my => INFLUENCE_SCALE,
+            c if c == op => -INFLUENCE_SCALE,
+            _ if mine < theirs => INFLUENCE_SCALE / (1 + mine),
+            _ if theirs < mine => -INFLUENCE_SCALE / (1 + theirs),
+            _ => 0,
+        };
+        let mut delta = 0;
+        for cy in 0..self.board_height {
+            for cx in 0..self.board_width {
+                delta += influence(after.at(cx, cy), my_after[cy][cx], op_after[cy][cx])
+                    - influence(self.board.at(cx, cy), my_before[cy][cx], op_before[cy][cx]);
+            }
+        }
+        delta
+    }
+
+    /// BFS steps through empty cells from every cell of one owner (`i32::MAX` if unreachable)
+    fn empty_distances_from(&self, source: Cell) -> Vec<Vec<i32>> {
+        self.empty_distances_on(&self.board, source)
+    }
+
+    /// Same as `empty_distances_from`, on an arbitrary board
+    fn empty_distances_on(&self, board: &Board, source: Cell) -> Vec<Vec<i32>> {
+        let mut dist = vec![vec![i32::MAX; self.board_width]; self.board_height];
+        let mut queue = VecDeque::new();
+        for (x, y, cell) in board.cells() {
+            if cell == source {
+                dist[y][x] = 0;
+                queue.push_back((x, y));
+            }
+        }
+
+        let directions = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in directions.iter() {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || ny < 0 || nx >= self.board_width as i32 || ny >= self.board_height as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if dist[ny][nx] == i32::MAX && board.at(nx, ny) == Cell::Empty {
+                    dist[ny][nx] = dist[y][x] + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        dist
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::testing::{board_state, frame};
+    use crate::types::Player;
+
+    #[test]
+    fn equidistant_cells_go_to_the_chosen_side() {
+        // (3, 0) is three steps from both
+        let state = board_state(&["@.....$"]);
+        assert_eq!(state.ownership_map(Player::One)[0][3], Some(Player::One));
+        assert_eq!(state.ownership_map(Player::Two)[0][3], Some(Player::Two));
+        assert_eq!(state.ownership_map(Player::One)[0][2], Some(Player::One));
+        assert_eq!(state.ownership_map(Player::Two)[0][4], Some(Player::Two));
+
+        assert_eq!(state.contested_counts(Player::One), (3, 2));
+        assert_eq!(state.contested_counts(Player::Two), (2, 3));
+        assert_eq!(state.signed_ownership(Player::Two)[0], vec![1, 1, 1, -1, -1, -1, -1]);
+    }
+
+    #[test]
+    fn expanding_toward_the_center_gains_more_influence_than_the_edge() {
+        let rows = [".........", ".........", ".........", ".........", ".@.......", ".........", ".........", ".........", "........$"];
+        let (state, offsets, _, _) = frame(&rows, &["##"]);
+        // Claiming (0, 4) against the wall, or (2, 4) toward the middle
+        let (edge, central) = (state.move_influence(0, 4, &offsets), state.move_influence(1, 4, &offsets));
+        assert!(0 < edge && edge < central, "edge {} central {}", edge, central);
+
+        // A move only onto our own cells changes nothing
+        assert_eq!(state.move_influence(1, 4, &offsets[..1]), 0);
+    }
+}
@@ -2,8 +2,10 @@
 // Core modules for the Filler game bot
 
 pub mod types;
+pub mod error;
 pub mod game;
 
 // Re-export commonly used types
 pub use types::*;
-pub use game::GameState;
+pub use error::ParseError;
+pub use game::{run_repl, Board, GameState, Grid, PackedBoard, ScoringConfig, TurnContext};
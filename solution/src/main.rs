@@ -1,11 +1,8 @@
 // Filler Bot - Modular Structure
 // A sophisticated Filler game bot with clean modular architecture
 
-mod types;
-mod game;
-
-use crate::game::GameState;
-use std::io::{self, BufRead, Write};
+use solution::{run_repl, GameState, Strategy};
+use std::io::{self, BufRead};
 
 /// Main function that handles the game loop for the Filler bot
 /// 
@@ -22,135 +19,37 @@ use std::io::{self, BufRead, Write};
 /// - Blocking effectiveness (proximity to opponent)
 /// - Compactness (adjacency to own territory)
 fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    // `bot --repl`: explore positions by hand instead of playing
+    if std::env::args().any(|arg| arg == "--repl") {
+        if let Err(e) = run_repl(stdin.lock(), &mut out) {
+            eprintln!("REPL error: {}", e);
+        }
+        return;
+    }
+
     // Initialize game state
     let mut game_state = GameState::new();
-    let stdin = io::stdin();
+    // FILLER_STRATEGY=max_area|max_block plays a baseline, for A/B self-play
+    if let Ok(name) = std::env::var("FILLER_STRATEGY") {
+        match Strategy::from_name(&name) {
+            Some(strategy) => game_state.strategy = strategy,
+            None => eprintln!("Ignoring unknown FILLER_STRATEGY: {}", name),
+        }
+    }
     let mut lines = stdin.lock().lines();
-    
-    // Process input until EOF
-    while let Some(line_result) = lines.next() {
-        // Handle potential I/O errors
-        let line = match line_result {
-            Ok(l) => l,
+
+    // Process turns until EOF
+    loop {
+        match game_state.play_turn(&mut lines, &mut out) {
+            Ok(true) => {}
+            Ok(false) => break,
             Err(e) => {
-                eprintln!("Error reading line: {}", e);
-                // Output a safe default move on error
-                println!("0 0");
-                io::stdout().flush().unwrap();
-                continue;
-            }
-        };
-        
-        // Process the input line
-        
-        if line.starts_with("$$$ exec p") {
-            game_state.parse_player(&line);
-        }
-        // Parse board dimensions
-        else if line.starts_with("Anfield ") {
-            if let Err(e) = game_state.parse_board_dimensions(&line) {
-                eprintln!("Error parsing board dimensions: {}", e);
-                // Output a safe default move on error
-                println!("0 0");
-                io::stdout().flush().unwrap();
-                continue;
-            }
-            
-            // Skip the column header line (e.g., "    01234567890123456789")
-            match lines.next() {
-                Some(Ok(_header_line)) => {
-                    // Header line skipped
-                },
-                _ => {
-                    eprintln!("Error: Expected header line after board dimensions");
-                    println!("0 0");
-                    io::stdout().flush().unwrap();
-                    continue;
-                }
-            }
-            
-            // Read board rows
-            let mut board_error = false;
-            for row_idx in 0..game_state.board_height {
-                match lines.next() {
-                    Some(Ok(board_line)) => {
-                        if let Err(e) = game_state.parse_board_row(&board_line, row_idx) {
-                            eprintln!("Error parsing board row {}: {}", row_idx, e);
-                            board_error = true;
-                        }
-                    },
-                    _ => {
-                        eprintln!("Unexpected end of input while reading board");
-                        board_error = true;
-                        break;
-                    }
-                }
-            }
-            
-            if board_error {
-                // Output a safe default move on error
-                println!("0 0");
-                io::stdout().flush().unwrap();
-                continue;
-            }
-            
-            // Look for piece info
-            let mut found_piece = false;
-            while let Some(line_result) = lines.next() {
-                let next_line = match line_result {
-                    Ok(l) => l,
-                    Err(e) => {
-                        eprintln!("Error reading line: {}", e);
-                        break;
-                    }
-                };
-                
-                if next_line.starts_with("Piece ") {
-                    found_piece = true;
-                    
-                    if let Err(e) = game_state.parse_piece_dimensions(&next_line) {
-                        eprintln!("Error parsing piece dimensions: {}", e);
-                        break;
-                    }
-                    
-                    // Read piece rows
-                    let mut piece_error = false;
-                    for row_idx in 0..game_state.piece_height {
-                        match lines.next() {
-                            Some(Ok(piece_line)) => {
-                                if let Err(e) = game_state.parse_piece_row(&piece_line, row_idx) {
-                                    eprintln!("Error parsing piece row {}: {}", row_idx, e);
-                                    piece_error = true;
-                                }
-                            },
-                            _ => {
-                                eprintln!("Unexpected end of input while reading piece");
-                                piece_error = true;
-                                break;
-                            }
-                        }
-                    }
-                    
-                    if piece_error {
-                        // Output a safe default move on error
-                        println!("0 0");
-                        io::stdout().flush().unwrap();
-                        break;
-                    }
-                    
-                    // Trim the piece to its minimal bounding box and get precomputed offsets
-                    let (piece_offsets, trim_off_x, trim_off_y) = game_state.trim_piece();
-                    
-                    // Make a move using the precomputed offsets
-                    game_state.make_move(&piece_offsets, trim_off_x, trim_off_y);
-                    break;
-                }
-            }
-            
-            if !found_piece {
-                // If we didn't find a piece, output a safe default move
-                println!("0 0");
-                io::stdout().flush().unwrap();
+                eprintln!("Error writing move: {}", e);
+                break;
             }
         }
     }
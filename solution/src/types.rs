@@ -50,6 +50,12 @@ impl PartialOrd for ScoredMove {
     }
 }
 
+/// Orders by score, then prefers the top-left anchor
+///
+/// The tiebreak compares the (possibly trimmed) anchors directly. Moves for
+/// one piece share a single trim offset, and the engine-facing coordinates
+/// are the anchors minus that offset, so the order is the same as on the
+/// output coordinates, negative anchors included.
 impl Ord for ScoredMove {
     fn cmp(&self, other: &Self) -> Ordering {
         // Primary: score (higher is better)
@@ -69,9 +75,176 @@ impl Ord for ScoredMove {
     }
 }
 
+/// A placement's score split into its weighted terms
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveAnalysis {
+    pub x: i32,
+    pub y: i32,
+    /// Total, as `score_move` returns it
+    pub score: i32,
+    /// Newly claimed cells
+    pub new_cells: i64,
+    /// Empty neighbors of the claimed cells
+    pub liberties: i64,
+    /// Contact with the opponent (blocking), including the catch-up bonus when behind
+    pub adj_op: i64,
+    /// Distance to the opponent (negative: closer is better)
+    pub heat_sum: i64,
+    /// Closeness to our existing territory
+    pub connectivity: i64,
+    /// Every remaining term (frontier, reachability, mobility, ...)
+    pub other: i64,
+}
+
 /// Represents a filled cell in the piece with its relative coordinates
-#[derive(Debug, Clone, Copy)]
+#[cfg(not(feature = "compact-offsets"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PieceOffset {
     pub dx: i32,
     pub dy: i32,
 }
+
+#[cfg(not(feature = "compact-offsets"))]
+impl PieceOffset {
+    pub fn new(dx: i32, dy: i32) -> Self {
+        PieceOffset { dx, dy }
+    }
+
+    pub fn dx(self) -> i32 {
+        self.dx
+    }
+
+    pub fn dy(self) -> i32 {
+        self.dy
+    }
+}
+
+/// Piece offset packed into two bytes
+///
+/// Trimmed offsets never exceed the piece dimensions, which the parser caps
+/// at `CompactOffset::MAX_PIECE_DIM` when the `compact-offsets` feature makes
+/// this the `PieceOffset` used everywhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactOffset {
+    pub dx: i8,
+    pub dy: i8,
+}
+
+impl CompactOffset {
+    /// Largest piece width or height whose offsets fit in an `i8`
+    pub const MAX_PIECE_DIM: usize = i8::MAX as usize + 1;
+
+    /// Offset from wide coordinates; callers keep them within `MAX_PIECE_DIM`
+    pub fn new(dx: i32, dy: i32) -> Self {
+        debug_assert!((0..Self::MAX_PIECE_DIM as i32).contains(&dx) && (0..Self::MAX_PIECE_DIM as i32).contains(&dy));
+        CompactOffset { dx: dx as i8, dy: dy as i8 }
+    }
+
+    pub fn dx(self) -> i32 {
+        i32::from(self.dx)
+    }
+
+    pub fn dy(self) -> i32 {
+        i32::from(self.dy)
+    }
+}
+
+/// With `compact-offsets`, pieces are stored two bytes per cell for cache density
+#[cfg(feature = "compact-offsets")]
+pub type PieceOffset = CompactOffset;
+
+/// How to choose between moves with equal scores
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TieBreak {
+    /// Prefer lower y, then lower x (the `ScoredMove` ordering)
+    TopLeft,
+    /// During early expansion, prefer the placement closest to the board center
+    CenterBias,
+}
+
+/// Order the legal-move scan visits anchors in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanOrder {
+    /// Left to right within a row, rows top to bottom
+    RowMajor,
+    /// Top to bottom within a column, columns left to right
+    ColumnMajor,
+}
+
+/// Dominant direction of a shape or of open space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+    /// Neither direction dominates
+    None,
+}
+
+/// Why a placement is not legal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IllegalReason {
+    /// Part of the piece falls off the board
+    OutOfBounds,
+    /// The piece covers opponent cells (more than the capture rule allows)
+    OpponentOverlap,
+    /// The piece covers fewer of our cells than required (usually none)
+    NoOwnOverlap,
+    /// The piece covers more of our cells than required (usually more than one)
+    MultipleOwnOverlaps,
+}
+
+/// Whether a placement may cover opponent cells (variant rule)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptureRule {
+    /// Standard Filler: no opponent cell may be covered
+    Forbidden,
+    /// A placement may cover (and capture) exactly one opponent cell
+    AllowOne,
+}
+
+/// How move coordinates are written; the default is what the engine expects
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OutputFormat {
+    /// Write `y x` instead of `x y`
+    pub swap_xy: bool,
+    /// Count rows up from the bottom edge instead of down from the top
+    pub flip_y: bool,
+}
+
+/// How moves are chosen; the simple ones are baselines for self-play comparisons
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strategy {
+    /// The full weighted heuristic
+    Heuristic,
+    /// Claim as many empty cells as possible, nothing else
+    MaxArea,
+    /// Touch as many opponent cells as possible, nothing else
+    MaxBlock,
+}
+
+impl Strategy {
+    /// Strategy by its config name (`heuristic`, `max_area`, `max_block`)
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        match name {
+            "heuristic" => Some(Strategy::Heuristic),
+            "max_area" => Some(Strategy::MaxArea),
+            "max_block" => Some(Strategy::MaxBlock),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_offsets_hold_every_piece_coordinate() {
+        let max = CompactOffset::MAX_PIECE_DIM as i32;
+        for d in 0..max {
+            let off = CompactOffset::new(d, max - 1 - d);
+            assert_eq!((off.dx(), off.dy()), (d, max - 1 - d));
+            assert_eq!(off, CompactOffset { dx: d as i8, dy: (max - 1 - d) as i8 });
+        }
+    }
+}